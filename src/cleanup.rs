@@ -0,0 +1,93 @@
+use clap::Args;
+use color_eyre::Result;
+use console::Term;
+use itertools::Itertools;
+use std::io::Write;
+use std::process::exit;
+
+use crate::project::document::Document;
+use crate::save::{needs_cleanup, render_dependency, save_dependency};
+
+#[derive(Args)]
+pub struct CleanupArgs {
+    /// only list the dependencies which would be cleaned up
+    #[arg(long, short)]
+    dry_run: bool,
+    /// exit with a non-zero code if any dependency needs a clean up
+    #[arg(long)]
+    check: bool,
+}
+
+pub fn cleanup(mut document: Document, args: CleanupArgs) -> Result<()> {
+    let mut term = Term::stdout();
+
+    let package_names = document
+        .get_packages()
+        .iter()
+        .map(|package| package.name.to_string())
+        .sorted()
+        .collect_vec();
+
+    let mut count = 0;
+
+    for package_name in package_names {
+        let lines = cleanup_package(&mut document, &package_name, args.dry_run)?;
+
+        if lines.is_empty() {
+            continue;
+        }
+
+        if document.is_workspace() {
+            writeln!(term, "{}", package_name.trim())?;
+        }
+
+        for line in &lines {
+            writeln!(term, "- {}", line)?;
+        }
+
+        count += lines.len();
+    }
+
+    if count == 0 {
+        writeln!(term, "nothing to clean up - all feature lists are minimal")?;
+    }
+
+    if args.check && count > 0 {
+        exit(1);
+    }
+
+    Ok(())
+}
+
+/// rewrites the features of the dependencies which list duplicates or features enabled anyway - returns their new lines
+///
+/// the enabled features stay the same - only the way they are written changes
+pub fn cleanup_package(
+    document: &mut Document,
+    package_name: &str,
+    dry_run: bool,
+) -> Result<Vec<String>> {
+    let dep_names = document
+        .get_package(package_name)?
+        .dependencies
+        .iter()
+        .map(|dependency| dependency.get_name())
+        .sorted()
+        .collect_vec();
+
+    let mut lines = vec![];
+
+    for dep_name in dep_names {
+        if !needs_cleanup(document, package_name, &dep_name)? {
+            continue;
+        }
+
+        lines.push(render_dependency(document, package_name, &dep_name)?);
+
+        if !dry_run {
+            save_dependency(document, package_name, &dep_name)?;
+        }
+    }
+
+    Ok(lines)
+}
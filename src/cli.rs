@@ -0,0 +1,659 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::process::exit;
+use std::sync::Mutex;
+use std::{env, io};
+
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::{generate, Shell};
+use color_eyre::eyre::{bail, WrapErr};
+use color_eyre::Result;
+use console::{style, Term};
+use itertools::Itertools;
+use tracing::Level;
+
+use crate::cleanup::{cleanup, CleanupArgs};
+use crate::enforce::{enforce, EnforceArgs};
+use crate::project::document::Document;
+use crate::prune::{defaults, minimize, prune, DefaultsArgs, MinimizeArgs, PruneArgs};
+use crate::rendering::display::Display;
+use crate::resolved::{resolved, ResolvedArgs};
+use crate::save::{render_dependency, save_dependency};
+use crate::tree::{tree, TreeArgs};
+
+#[derive(Parser)]
+#[command(name = "cargo")]
+#[command(bin_name = "cargo")]
+enum CargoCli {
+    Features(FeaturesArgs),
+}
+
+#[derive(Parser)]
+#[command(author, version, about, long_about = None)]
+struct FeaturesArgs {
+    /// print a completion script - `--dependency` is completed with the dependencies of the project
+    #[arg(long = "generate", value_enum)]
+    generator: Option<Shell>,
+
+    /// print the dependencies of the project - one per line, used by the completion scripts
+    #[arg(long, hide = true)]
+    list_dependencies: bool,
+
+    /// the dependency to open - `package::dependency` picks the package in a workspace
+    #[arg(long, short)]
+    dependency: Option<String>,
+
+    /// only use the dependency of this package - in a workspace several packages can have the same dependency
+    #[arg(long, short)]
+    package: Option<String>,
+
+    /// forbid cargo to access the network or update the Cargo.lock - like `cargo --frozen`
+    #[arg(long, global = true)]
+    frozen: bool,
+
+    /// log cargo invocations, checks & writes of manifests - to a file in the temp dir while the selector is open
+    #[arg(long, short, global = true)]
+    verbose: bool,
+
+    /// enable all features of the dependency and save without opening the selector
+    #[arg(long, requires = "dependency")]
+    all_features: bool,
+
+    /// disable the default features of the dependency and save without opening the selector
+    #[arg(long, requires = "dependency")]
+    no_default_features: bool,
+
+    /// print the enabled features of the dependency - one per line - without opening the selector
+    #[arg(long, requires = "dependency", conflicts_with_all = ["all_features", "no_default_features"])]
+    print_enabled: bool,
+
+    /// open the selector without ever changing a manifest - toggling features is disabled
+    #[arg(long, conflicts_with_all = ["all_features", "no_default_features"])]
+    read_only: bool,
+
+    /// keep all changes in memory - on exit the diff is shown and only applied to the manifests after confirmation
+    #[arg(long, conflicts_with_all = ["all_features", "no_default_features", "read_only"])]
+    scratch: bool,
+
+    #[command(subcommand)]
+    sub: Option<FeaturesSubCommands>,
+}
+
+#[derive(Subcommand)]
+enum FeaturesSubCommands {
+    /// disable every enabled feature the project still compiles & passes its tests without
+    Prune(PruneArgs),
+    /// prune all features and report how many could be removed before applying the changes
+    Minimize(MinimizeArgs),
+    /// check which dependencies compile without their default features & suggest the features replacing them
+    Defaults(DefaultsArgs),
+    /// show which packages of the workspace enable which features of a dependency
+    Tree(TreeArgs),
+    /// show the features cargo compiles the dependencies with after unifying the whole dependency graph
+    Resolved(ResolvedArgs),
+    /// remove duplicate features & features which are enabled anyway (e.g. by default) from the manifests
+    Cleanup(CleanupArgs),
+    /// enable the features listed in `cargo-features-manager.keep` which are disabled in the manifests
+    Enforce(EnforceArgs),
+}
+
+/// parses the arguments of `cargo features` & runs the selected command
+pub fn main() -> Result<()> {
+    color_eyre::install()?;
+
+    let CargoCli::Features(args) = CargoCli::parse();
+
+    if let Some(generator) = args.generator {
+        eprintln!("Generating completion file for {generator:?}...");
+        print!("{}", completion_script(generator)?);
+        return Ok(());
+    }
+
+    if args.list_dependencies {
+        // completions must never wait for the network - like `cargo --offline` a missing or outdated Cargo.lock is still written
+        env::set_var("CARGO_NET_OFFLINE", "true");
+        return list_dependencies(&Document::new(false)?);
+    }
+
+    if args.verbose {
+        init_logging(args.is_interactive())?;
+    }
+
+    run(args)
+}
+
+impl FeaturesArgs {
+    /// whether the selector is opened
+    fn is_interactive(&self) -> bool {
+        self.sub.is_none() && !self.print_enabled && !self.all_features && !self.no_default_features
+    }
+}
+
+/// logs to stderr - or to a file while the selector is open, the log would be drawn over otherwise
+fn init_logging(interactive: bool) -> Result<()> {
+    let logger = tracing_subscriber::fmt()
+        .with_max_level(Level::DEBUG)
+        .with_target(false);
+
+    if interactive {
+        let path = env::temp_dir().join("cargo-features-manager.log");
+        let file =
+            File::create(&path).wrap_err_with(|| format!("could not create {}", path.display()))?;
+
+        eprintln!("logging to {}", path.display());
+        logger.with_writer(Mutex::new(file)).init();
+    } else {
+        logger.with_writer(io::stderr).init();
+    }
+
+    Ok(())
+}
+
+/// the script of clap - with the values of `--dependency` completed by `--list-dependencies` where the shell allows it
+fn completion_script(shell: Shell) -> Result<String> {
+    let cmd = &mut FeaturesArgs::command();
+
+    let mut script = vec![];
+    generate(shell, cmd, cmd.get_name().to_string(), &mut script);
+    let script = String::from_utf8(script)?;
+
+    let list = "cargo features --list-dependencies 2>/dev/null";
+
+    Ok(match shell {
+        Shell::Bash => {
+            let mut lines = script.lines().map(String::from).collect_vec();
+
+            for index in 1..lines.len() {
+                let option = lines[index - 1].trim();
+
+                if option == "--dependency)" || option == "-d)" {
+                    lines[index] = lines[index].replace(
+                        "compgen -f \"${cur}\"",
+                        &format!("compgen -W \"$({})\" -- \"${{cur}}\"", list),
+                    );
+                }
+            }
+
+            lines.join("\n") + "\n"
+        }
+        Shell::Zsh => script.replace(
+            ":DEPENDENCY:_default'",
+            &format!(":DEPENDENCY:{{compadd -- $({})}}'", list),
+        ),
+        Shell::Fish => {
+            script
+                .lines()
+                .map(|line| {
+                    if line.contains("-s d -l dependency") && line.ends_with(" -r") {
+                        format!("{} -f -a \"({})\"", line, list)
+                    } else {
+                        line.to_string()
+                    }
+                })
+                .join("\n")
+                + "\n"
+        }
+        _ => script,
+    })
+}
+
+/// the names of all dependencies - in a workspace also as `package::dependency`
+///
+/// the crate names are used - a dev & a normal dependency on the same crate are listed once
+fn list_dependencies(document: &Document) -> Result<()> {
+    let packages = document
+        .get_packages()
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| Some(*index) != document.workspace_index())
+        .map(|(_, package)| package)
+        .collect_vec();
+
+    let names = packages
+        .iter()
+        .flat_map(|package| &package.dependencies)
+        .filter(|dependency| !dependency.is_own_features())
+        .map(|dependency| dependency.name.to_string());
+
+    let qualified = packages
+        .iter()
+        .filter(|_| document.is_workspace())
+        .flat_map(|package| {
+            package
+                .dependencies
+                .iter()
+                .filter(|dependency| !dependency.is_own_features())
+                .map(|dependency| format!("{}::{}", package.name, dependency.name))
+        });
+
+    for name in names.sorted().dedup().chain(qualified.sorted().dedup()) {
+        println!("{}", name);
+    }
+
+    Ok(())
+}
+
+fn run(args: FeaturesArgs) -> Result<()> {
+    let document = Document::new_with_progress(args.frozen)?;
+
+    // the interface shows them as a notice instead
+    if !args.is_interactive() {
+        for error in document.get_load_errors() {
+            eprintln!("{} {}", style("warning:").yellow(), error);
+        }
+    }
+
+    let (package, dependency) = split_dependency(args.package, args.dependency)?;
+
+    if let Some(package) = &package {
+        check_package(&document, package)?;
+    }
+
+    if let Some(sub) = args.sub {
+        match sub {
+            FeaturesSubCommands::Prune(args) => {
+                prune(document, args)?;
+            }
+            FeaturesSubCommands::Minimize(args) => {
+                minimize(document, args)?;
+            }
+            FeaturesSubCommands::Defaults(args) => {
+                defaults(document, args)?;
+            }
+            FeaturesSubCommands::Tree(args) => {
+                tree(document, args)?;
+            }
+            FeaturesSubCommands::Resolved(args) => {
+                resolved(document, args)?;
+            }
+            FeaturesSubCommands::Cleanup(args) => {
+                cleanup(document, args)?;
+            }
+            FeaturesSubCommands::Enforce(args) => {
+                enforce(document, args)?;
+            }
+        }
+    } else if args.print_enabled {
+        print_enabled(
+            &document,
+            package.as_deref(),
+            &dependency.unwrap_or_default(),
+        )?;
+    } else if args.all_features || args.no_default_features {
+        let name = dependency.unwrap_or_default();
+
+        quick_set(
+            document,
+            package.as_deref(),
+            &name,
+            args.all_features,
+            args.no_default_features,
+        )?;
+    } else {
+        let mut document = document;
+
+        if args.scratch {
+            document.start_scratch();
+        }
+
+        let mut display = Display::new(document)?;
+        display.set_read_only(args.read_only);
+
+        match (package, dependency) {
+            (package, Some(name)) => display.set_selected_dep(package.as_deref(), name)?,
+            (Some(package), None) => display.set_selected_package(&package)?,
+            (None, None) => {}
+        }
+
+        let _ = ctrlc::set_handler(|| {
+            let term = Term::stdout();
+            term.show_cursor().expect("could not enable cursor");
+
+            exit(0);
+        });
+
+        display.start()?;
+
+        if args.scratch {
+            apply_scratch(display.take_scratch())?;
+        }
+    }
+
+    Ok(())
+}
+
+/// prints the diff of each manifest changed in scratch mode - then asks to write them
+fn apply_scratch(scratch: BTreeMap<String, String>) -> Result<()> {
+    let mut changes = vec![];
+
+    for (manifest_path, content) in scratch {
+        let before = fs::read_to_string(&manifest_path)
+            .wrap_err_with(|| format!("could not read {}", manifest_path))?;
+
+        if before != content {
+            changes.push((manifest_path, before, content));
+        }
+    }
+
+    if changes.is_empty() {
+        println!("nothing was changed");
+        return Ok(());
+    }
+
+    for (manifest_path, before, content) in &changes {
+        println!("{}", style(manifest_path).bold());
+
+        for line in diff_lines(before, content) {
+            match line.chars().next() {
+                Some('-') => println!("{}", style(line).red()),
+                Some('+') => println!("{}", style(line).green()),
+                _ => println!("{}", line),
+            }
+        }
+
+        println!();
+    }
+
+    let term = Term::stdout();
+    print!("apply these changes? [y/N] ");
+    io::Write::flush(&mut io::stdout())?;
+
+    let answer = term.read_char()?;
+    println!();
+
+    if !answer.eq_ignore_ascii_case(&'y') {
+        println!("discarded the changes");
+        return Ok(());
+    }
+
+    for (manifest_path, _, content) in changes {
+        fs::write(&manifest_path, content)
+            .wrap_err_with(|| format!("could not write {}", manifest_path))?;
+    }
+
+    Ok(())
+}
+
+/// the changed lines between both texts - `-` for removed & `+` for added lines with one unchanged line around them
+///
+/// `...` separates changes which are further apart
+fn diff_lines(before: &str, after: &str) -> Vec<String> {
+    let before = before.lines().collect_vec();
+    let after = after.lines().collect_vec();
+
+    // the longest common subsequence of the remaining lines of both texts
+    let mut lengths = vec![vec![0; after.len() + 1]; before.len() + 1];
+
+    for i in (0..before.len()).rev() {
+        for j in (0..after.len()).rev() {
+            lengths[i][j] = if before[i] == after[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut lines = vec![];
+    let (mut i, mut j) = (0, 0);
+
+    while i < before.len() || j < after.len() {
+        if i < before.len() && j < after.len() && before[i] == after[j] {
+            lines.push((' ', before[i]));
+            i += 1;
+            j += 1;
+        } else if i < before.len() && (j == after.len() || lengths[i + 1][j] >= lengths[i][j + 1]) {
+            lines.push(('-', before[i]));
+            i += 1;
+        } else {
+            lines.push(('+', after[j]));
+            j += 1;
+        }
+    }
+
+    let is_near_change = |index: usize| {
+        (index.saturating_sub(1)..=index + 1)
+            .filter_map(|index| lines.get(index))
+            .any(|(kind, _)| *kind != ' ')
+    };
+
+    let mut diff = vec![];
+    let mut last = None;
+
+    for (index, (kind, line)) in lines.iter().enumerate() {
+        if !is_near_change(index) {
+            continue;
+        }
+
+        if last.map_or(false, |last| last + 1 != index) {
+            diff.push("...".to_string());
+        }
+
+        diff.push(format!("{}{}", kind, line));
+        last = Some(index);
+    }
+
+    diff
+}
+
+/// splits `package::dependency` - a qualifier has to match `--package`
+fn split_dependency(
+    package: Option<String>,
+    dependency: Option<String>,
+) -> Result<(Option<String>, Option<String>)> {
+    let Some((qualifier, name)) = dependency.as_deref().and_then(|name| name.split_once("::"))
+    else {
+        return Ok((package, dependency));
+    };
+
+    if let Some(package) = &package {
+        if package != qualifier {
+            bail!(
+                "the dependency is qualified with package \"{}\" but --package is \"{}\"",
+                qualifier,
+                package
+            );
+        }
+    }
+
+    Ok((Some(qualifier.to_string()), Some(name.to_string())))
+}
+
+fn check_package(document: &Document, name: &str) -> Result<()> {
+    if document.get_package(name).is_err() {
+        let packages = document
+            .get_packages()
+            .iter()
+            .enumerate()
+            .filter(|(index, _)| Some(*index) != document.workspace_index())
+            .map(|(_, package)| package.name.as_str())
+            .sorted()
+            .join(", ");
+
+        bail!(
+            "package \"{}\" could not be found - the packages are: {}",
+            name,
+            packages
+        );
+    }
+
+    Ok(())
+}
+
+/// prints the enabled features of the dependency - combined over all packages like cargo unifies them
+///
+/// without a package the features of all packages are combined
+fn print_enabled(document: &Document, package: Option<&str>, name: &str) -> Result<()> {
+    let dependencies = document
+        .get_packages()
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| Some(*index) != document.workspace_index())
+        .filter(|(_, other)| package.map_or(true, |package| other.name == package))
+        .flat_map(|(_, package)| &package.dependencies)
+        .filter(|dependency| dependency.name == name && !dependency.is_own_features())
+        .collect::<Vec<_>>();
+
+    if dependencies.is_empty() {
+        bail!("dependency \"{}\" could not be found", name);
+    }
+
+    let features = dependencies
+        .iter()
+        .flat_map(|dependency| &dependency.features)
+        .filter(|(name, data)| *name != "default" && data.is_enabled())
+        .map(|(name, _)| name)
+        .sorted()
+        .dedup();
+
+    for feature in features {
+        println!("{}", feature);
+    }
+
+    Ok(())
+}
+
+/// applies `--all-features` & `--no-default-features` to the dependency in every package and prints the saved entries
+fn quick_set(
+    mut document: Document,
+    package: Option<&str>,
+    name: &str,
+    all_features: bool,
+    no_default_features: bool,
+) -> Result<()> {
+    let mut targets = document
+        .get_packages()
+        .iter()
+        .filter(|other| package.map_or(true, |package| other.name == package))
+        .flat_map(|package| {
+            package
+                .dependencies
+                .iter()
+                .filter(|dependency| dependency.name == name && !dependency.is_own_features())
+                .map(|dependency| (package.name.to_string(), dependency.get_name()))
+        })
+        .collect::<Vec<_>>();
+
+    if targets.is_empty() {
+        bail!("dependency \"{}\" could not be found", name);
+    }
+
+    // cargo rejects `default-features` next to `workspace = true` - the workspace definition is changed instead
+    let inherits = targets.iter().any(|(package_name, dep_name)| {
+        document
+            .get_package(package_name)
+            .and_then(|package| package.get_dep(dep_name))
+            .map_or(false, |dependency| dependency.workspace)
+    });
+
+    if no_default_features && inherits {
+        if let Some(workspace_index) = document.workspace_index() {
+            let workspace = document.get_package_by_id(workspace_index)?;
+
+            if let Some(dependency) = workspace
+                .dependencies
+                .iter()
+                .find(|dependency| dependency.name == name)
+            {
+                let target = (workspace.name.to_string(), dependency.get_name());
+
+                if !targets.contains(&target) {
+                    targets.push(target);
+                }
+            }
+        }
+    }
+
+    for (package_name, dep_name) in targets {
+        let dependency = document
+            .get_package_mut(&package_name)?
+            .get_dep_mut(&dep_name)?;
+
+        if no_default_features && dependency.workspace {
+            println!(
+                "{}: {} inherits default-features from [workspace.dependencies] - only changed there",
+                package_name, dependency.name
+            );
+        } else if no_default_features {
+            dependency.disable_default_features()?;
+        }
+
+        if all_features {
+            dependency.enable_all_features()?;
+        }
+
+        save_dependency(&mut document, &package_name, &dep_name)?;
+
+        println!(
+            "{}: {}",
+            package_name,
+            render_dependency(&document, &package_name, &dep_name)?
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::{completion_script, diff_lines, split_dependency};
+    use clap_complete::Shell;
+
+    fn some(text: &str) -> Option<String> {
+        Some(text.to_string())
+    }
+
+    #[test]
+    fn diffs_the_changed_lines() {
+        let before = "[package]\nname = \"a\"\n\n[dependencies]\nclap = \"4\"\nlog = \"0.4\"\nrand = \"0.8\"\nserde = \"1\"\ntokio = \"1\"\n";
+        let after = "[package]\nname = \"a\"\n\n[dependencies]\nclap = { version = \"4\", features = [\"derive\"] }\nlog = \"0.4\"\nrand = \"0.8\"\nserde = \"1\"\ntokio = { version = \"1\", features = [\"rt\"] }\n";
+
+        assert_eq!(
+            diff_lines(before, after),
+            [
+                " [dependencies]",
+                "-clap = \"4\"",
+                "+clap = { version = \"4\", features = [\"derive\"] }",
+                " log = \"0.4\"",
+                "...",
+                " serde = \"1\"",
+                "-tokio = \"1\"",
+                "+tokio = { version = \"1\", features = [\"rt\"] }",
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_the_package_from_the_dependency() {
+        assert_eq!(
+            split_dependency(None, some("app::serde")).unwrap(),
+            (some("app"), some("serde"))
+        );
+        assert_eq!(
+            split_dependency(some("app"), some("serde")).unwrap(),
+            (some("app"), some("serde"))
+        );
+        assert_eq!(
+            split_dependency(some("app"), some("app::serde")).unwrap(),
+            (some("app"), some("serde"))
+        );
+        assert!(split_dependency(some("lib"), some("app::serde")).is_err());
+    }
+
+    #[test]
+    fn completes_the_dependencies_of_the_project() {
+        let list = "cargo features --list-dependencies 2>/dev/null";
+
+        for shell in [Shell::Bash, Shell::Zsh, Shell::Fish] {
+            let script = completion_script(shell).unwrap();
+
+            assert!(script.contains(list), "{:?}", shell);
+        }
+
+        assert!(!completion_script(Shell::Bash)
+            .unwrap()
+            .contains("--dependency)\n                    COMPREPLY=($(compgen -f"));
+    }
+}
@@ -0,0 +1,189 @@
+use clap::Args;
+use color_eyre::Result;
+use console::{style, Term};
+use itertools::Itertools;
+use std::io::Write;
+use std::process::exit;
+
+use crate::project::document::Document;
+use crate::save::save_dependency;
+
+#[derive(Args)]
+pub struct EnforceArgs {
+    /// only list the features which would be enabled
+    #[arg(long, short)]
+    dry_run: bool,
+    /// exit with a non-zero code if any kept feature is disabled
+    #[arg(long)]
+    check: bool,
+}
+
+/// the kept features of one dependency of a package which are disabled or do not exist
+struct Enforcement {
+    dep_name: String,
+    features: Vec<String>,
+    unknown: Vec<String>,
+}
+
+pub fn enforce(mut document: Document, args: EnforceArgs) -> Result<()> {
+    let mut term = Term::stdout();
+
+    let package_names = document
+        .get_packages()
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| Some(*index) != document.workspace_index())
+        .map(|(_, package)| package.name.to_string())
+        .sorted()
+        .collect_vec();
+
+    let mut count = 0;
+
+    for package_name in package_names {
+        let enforcements = get_enforcements(&document, &package_name)?;
+
+        for enforcement in &enforcements {
+            for feature in &enforcement.unknown {
+                writeln!(
+                    term,
+                    "{} {} of {} does not have the kept feature {}",
+                    style("warning:").yellow(),
+                    enforcement.dep_name,
+                    package_name,
+                    feature
+                )?;
+            }
+        }
+
+        let enforcements = enforcements
+            .into_iter()
+            .filter(|enforcement| !enforcement.features.is_empty())
+            .collect_vec();
+
+        if enforcements.is_empty() {
+            continue;
+        }
+
+        if document.is_workspace() {
+            writeln!(term, "{}", package_name)?;
+        }
+
+        for enforcement in &enforcements {
+            writeln!(
+                term,
+                "- {}: {}",
+                enforcement.dep_name,
+                enforcement
+                    .features
+                    .iter()
+                    .map(|feature| format!("+{}", feature))
+                    .join(" ")
+            )?;
+
+            if args.dry_run {
+                continue;
+            }
+
+            let dependency = document
+                .get_package_mut(&package_name)?
+                .get_dep_mut(&enforcement.dep_name)?;
+
+            for feature in &enforcement.features {
+                dependency.enable_feature(feature)?;
+            }
+
+            save_dependency(&mut document, &package_name, &enforcement.dep_name)?;
+        }
+
+        count += enforcements.len();
+    }
+
+    if count == 0 {
+        writeln!(term, "nothing to enforce - all kept features are enabled")?;
+    }
+
+    if args.check && count > 0 {
+        exit(1);
+    }
+
+    Ok(())
+}
+
+/// returns the kept features which are disabled or unknown per dependency - dependencies without such features are skipped
+///
+/// the entries of the workspace apply to every package - features enabled by another feature count as enabled
+fn get_enforcements(document: &Document, package_name: &str) -> Result<Vec<Enforcement>> {
+    let package = document.get_package(package_name)?;
+
+    let mut enforcements = vec![];
+
+    for dependency in package.get_deps() {
+        if dependency.is_own_features() {
+            continue;
+        }
+
+        let kept = document
+            .workspace_keep()
+            .get(&dependency.name)
+            .into_iter()
+            .chain(package.keep.get(&dependency.name))
+            .flat_map(|entry| entry.features.iter())
+            .sorted()
+            .dedup()
+            .collect_vec();
+
+        if kept.is_empty() {
+            continue;
+        }
+
+        let implied = dependency.get_implied_features();
+        let mut features = vec![];
+        let mut unknown = vec![];
+
+        for feature in kept {
+            match dependency.get_feature(feature) {
+                None => unknown.push(feature.to_string()),
+                Some(data) if !data.is_enabled() && !implied.contains(feature) => {
+                    features.push(feature.to_string())
+                }
+                Some(_) => {}
+            }
+        }
+
+        if !features.is_empty() || !unknown.is_empty() {
+            enforcements.push(Enforcement {
+                dep_name: dependency.get_name(),
+                features,
+                unknown,
+            });
+        }
+    }
+
+    Ok(enforcements)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::get_enforcements;
+    use crate::project::document::Document;
+    use crate::util::fixture_project;
+
+    #[test]
+    fn lists_the_disabled_and_unknown_kept_features() {
+        let dir = fixture_project(
+            "dep = { version = \"0.1\", default-features = false }\n\n\
+             [cargo-features-manager.keep]\n\
+             dep = [\"std\", \"serde\", \"default\", \"missing\"]",
+        );
+
+        let document = Document::from_manifest_path(dir.path().join("Cargo.toml"), true).unwrap();
+        let enforcements = get_enforcements(&document, "edit").unwrap();
+
+        assert_eq!(enforcements.len(), 1);
+        assert_eq!(enforcements[0].dep_name, "dep");
+        assert_eq!(enforcements[0].features, ["default", "serde", "std"]);
+        assert_eq!(enforcements[0].unknown, ["missing"]);
+    }
+}
@@ -0,0 +1,56 @@
+//! reading & editing the features of the dependencies of a cargo project - the library behind `cargo features`
+//!
+//! ```no_run
+//! use cargo_features_manager::{save_dependency, Document};
+//!
+//! let mut document = Document::from_manifest_path("path/to/Cargo.toml", false)?;
+//!
+//! let dependency = document.get_package_mut("my-package")?.get_dep_mut("tokio")?;
+//! dependency.enable_feature("rt-multi-thread")?;
+//!
+//! save_dependency(&mut document, "my-package", "tokio")?;
+//! # Ok::<(), color_eyre::Report>(())
+//! ```
+//!
+//! # stable api
+//!
+//! only the items re-exported here are stable - breaking changes to them only happen in a new minor version
+//!
+//! the structs are `#[non_exhaustive]` - new fields may be added to them in any release
+//!
+//! - [`Document`] - `new`, `from_manifest_path`, `get_packages`, `get_package`, `get_package_mut`,
+//!   `workspace_index`, `root_path`, `is_workspace`, `apply_features_to_all_packages`
+//! - [`Package`] - its fields, `get_deps`, `get_dep`, `get_dep_mut`, `get_dep_by_kind`, `get_dep_by_kind_mut`
+//! - [`Dependency`] - its fields, `get_name`, `get_feature`, `has_features`, `is_own_features`, `can_use_default`,
+//!   `get_features_to_enable`, `get_own_enabled_features`, `enable_feature`, `disable_feature`, `toggle_feature`,
+//!   `set_enabled_features`, `enable_all_features`, `disable_default_features`
+//! - [`FeatureData`] - its fields, `is_enabled`, `is_toggleable`
+//! - [`DependencyType`] - to look up the dev & build dependencies by their crate name
+//! - [`save_dependency`] & [`render_dependency`]
+//!
+//! every other public method may change with any release - the [`cli`] module is only meant for the binary
+
+#![warn(clippy::unwrap_used)]
+
+#[doc(hidden)]
+pub mod cli;
+
+mod cleanup;
+mod enforce;
+mod prune;
+mod rendering;
+
+mod parsing;
+mod project;
+mod util;
+
+mod resolved;
+mod save;
+mod tree;
+
+pub use parsing::keep::KeepEntry;
+pub use project::dependency::feature::{EnabledState, FeatureData, SubFeature, SubFeatureType};
+pub use project::dependency::{Dependency, DependencyType};
+pub use project::document::Document;
+pub use project::package::Package;
+pub use save::{render_dependency, save_dependency};
@@ -0,0 +1,128 @@
+use crate::project::dependency::Dependency;
+use crate::project::package::Package;
+use crate::util::{get_item_from_doc, toml_document_from_path};
+use color_eyre::eyre::{eyre, ContextCompat, WrapErr};
+use color_eyre::Result;
+use std::collections::HashMap;
+use std::path::Path;
+use toml_edit::Item;
+
+/// returns the groups of mutually exclusive features per dependency name - empty if the manifest or the section does not exist
+///
+/// e.g. `sqlx = [["runtime-tokio", "runtime-async-std"]]`
+pub fn get_conflicts<P: AsRef<Path>>(
+    dir_path: P,
+    item_path: &str,
+) -> Result<HashMap<String, Vec<Vec<String>>>> {
+    let Ok(document) = toml_document_from_path(dir_path.as_ref().join("Cargo.toml")) else {
+        return Ok(HashMap::new());
+    };
+
+    let Ok(item) = get_item_from_doc(item_path, &document) else {
+        return Ok(HashMap::new());
+    };
+
+    let table = item.as_table_like().context(format!(
+        "could not parse {} in {:?}",
+        item_path,
+        dir_path.as_ref()
+    ))?;
+
+    table
+        .iter()
+        .map(|(name, item)| {
+            parse_conflicts(item)
+                .map(|groups| (name.to_string(), groups))
+                .wrap_err_with(|| format!("could not parse {}.{}", item_path, name))
+        })
+        .collect()
+}
+
+pub fn parse_conflicts(item: &Item) -> Result<Vec<Vec<String>>> {
+    let groups = item
+        .as_array()
+        .ok_or(eyre!("invalid format of conflicts - not an array"))?;
+
+    groups
+        .iter()
+        .map(|group| {
+            let group = group.as_array().ok_or(eyre!(
+                "invalid format of conflicts - a group is not an array"
+            ))?;
+
+            group
+                .iter()
+                .map(|feature| {
+                    feature
+                        .as_str()
+                        .map(|feature| feature.to_string())
+                        .ok_or(eyre!(
+                            "invalid format of conflicts - a feature is not a string"
+                        ))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// marks the features of each group as conflicting with the other features of the group
+pub fn apply_conflicts(package: &mut Package, conflicts: &HashMap<String, Vec<Vec<String>>>) {
+    for dependency in &mut package.dependencies {
+        if dependency.is_own_features() {
+            continue;
+        }
+
+        if let Some(groups) = conflicts.get(&dependency.name) {
+            apply_conflict_groups(dependency, groups);
+        }
+    }
+}
+
+pub fn apply_conflict_groups(dependency: &mut Dependency, groups: &[Vec<String>]) {
+    for group in groups {
+        for feature in group {
+            let Some(data) = dependency.features.get_mut(feature) else {
+                continue;
+            };
+
+            for other in group {
+                if other != feature && !data.conflicts.contains(other) {
+                    data.conflicts.push(other.to_string());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::parse_conflicts;
+    use toml_edit::DocumentMut;
+
+    #[test]
+    fn parses_groups_of_conflicting_features() {
+        let doc: DocumentMut =
+            "sqlx = [[\"runtime-tokio\", \"runtime-async-std\"], [\"tls-rustls\", \"tls-native-tls\"]]"
+                .parse()
+                .unwrap();
+
+        assert_eq!(
+            parse_conflicts(&doc["sqlx"]).unwrap(),
+            [
+                ["runtime-tokio", "runtime-async-std"],
+                ["tls-rustls", "tls-native-tls"]
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_conflicts() {
+        let doc: DocumentMut = "a = \"rt\"\nb = [\"rt\"]\nc = [[1]]".parse().unwrap();
+
+        assert!(parse_conflicts(&doc["a"]).is_err());
+        assert!(parse_conflicts(&doc["b"]).is_err());
+        assert!(parse_conflicts(&doc["c"]).is_err());
+    }
+}
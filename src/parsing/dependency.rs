@@ -7,7 +7,7 @@ use cargo_metadata::PackageId;
 use color_eyre::eyre::{eyre, ContextCompat};
 use itertools::Itertools;
 use semver::VersionReq;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use toml_edit::Item;
 
 pub fn parse_dependency(
@@ -15,7 +15,7 @@ pub fn parse_dependency(
     packages: &HashMap<PackageId, cargo_metadata::Package>,
     document: &toml_edit::DocumentMut,
 ) -> color_eyre::Result<Dependency> {
-    let package = get_package_from_version(&dependency.name, &dependency.req, packages)?;
+    let package = get_dependency_package(dependency, packages)?;
 
     let kind: DependencyType = dependency.kind.into();
     let mut workspace = false;
@@ -58,8 +58,10 @@ pub fn parse_dependency(
             .to_string()
             .trim_start_matches('^')
             .to_owned(),
+        resolved_version: Some(package.version.to_string()),
         kind,
         workspace,
+        optional: dependency.optional,
         features: HashMap::new(),
         comment: None,
     };
@@ -74,79 +76,171 @@ pub fn parse_dependency(
     Ok(new_dependency)
 }
 
+/// the package the dependency resolves to - path & git dependencies are matched by their source first
+///
+/// e.g. a local fork next to the crates.io version of the same crate
+fn get_dependency_package<'a>(
+    dependency: &cargo_metadata::Dependency,
+    packages: &'a HashMap<PackageId, cargo_metadata::Package>,
+) -> color_eyre::Result<&'a cargo_metadata::Package> {
+    let git_source = dependency
+        .source
+        .as_deref()
+        .filter(|source| source.starts_with("git+"));
+
+    let by_source = packages.values().find(|package| {
+        if package.name != dependency.name {
+            return false;
+        }
+
+        if let Some(path) = &dependency.path {
+            return package.manifest_path.parent() == Some(path.as_path());
+        }
+
+        // the source of the package ends with the checked out commit - e.g. `#4f1a2b3`
+        git_source.map_or(false, |git_source| {
+            package
+                .source
+                .as_ref()
+                .map_or(false, |source| source.repr.starts_with(git_source))
+        })
+    });
+
+    match by_source {
+        Some(package) => Ok(package),
+        None => get_package_from_version(&dependency.name, &dependency.req, packages),
+    }
+}
+
 pub fn parse_dependency_from_item(
     packages: &HashMap<PackageId, cargo_metadata::Package>,
     name: &str,
     data: &Item,
 ) -> color_eyre::Result<Dependency> {
-    let mut version = "*";
-    let mut enabled_features = vec![];
-    let mut uses_default_features = true;
-    let mut rename = None;
+    let entry = parse_dependency_entry(data)?;
+
+    let mut dependency = Dependency {
+        name: name.to_string(),
+        rename: entry.package,
+        comment: None,
+        version: entry.version.clone(),
+        resolved_version: None,
+        workspace: false,
+        optional: false,
+        kind: DependencyType::Workspace,
+        target: None,
+        features: Default::default(),
+    };
+
+    if let Ok(package) =
+        get_package_from_version(name, &VersionReq::parse(&entry.version)?, packages)
+    {
+        dependency.resolved_version = Some(package.version.to_string());
+
+        set_features(
+            &mut dependency,
+            package,
+            entry.uses_default_features,
+            &entry.features,
+        )?;
+    } else {
+        dependency.comment = Some("unused".to_string());
+    }
+
+    Ok(dependency)
+}
+
+/// the feature relevant parts of a dependency entry in a manifest
+#[derive(Debug, PartialEq)]
+pub struct DependencyEntry {
+    pub version: String,
+    pub features: Vec<String>,
+    pub uses_default_features: bool,
+    pub package: Option<String>,
+}
+
+/// parses a dependency entry - either `name = "version"` or `name = { version = "...", features = [...], ... }`
+pub fn parse_dependency_entry(data: &Item) -> color_eyre::Result<DependencyEntry> {
+    let mut entry = DependencyEntry {
+        version: "*".to_string(),
+        features: vec![],
+        uses_default_features: true,
+        package: None,
+    };
 
     if let Some(data) = data.as_table_like() {
         //parse version
         if let Some(version_data) = data.get("version") {
-            version = version_data
+            entry.version = version_data
                 .as_str()
-                .ok_or(eyre!("could not parse version"))?;
+                .ok_or(eyre!("could not parse version"))?
+                .to_string();
         }
 
-        //parse enabled features
+        //parse enabled features - a single feature may be written as a plain string
         if let Some(features) = data.get("features") {
-            let features = features
-                .as_array()
-                .ok_or(eyre!("could not parse features"))?;
-
-            enabled_features = features
-                .iter()
-                .filter_map(|feature| feature.as_str())
-                .map(|feature| feature.to_string())
-                .collect();
+            entry.features = if let Some(feature) = features.as_str() {
+                vec![feature.to_string()]
+            } else {
+                features
+                    .as_array()
+                    .ok_or(eyre!("could not parse features"))?
+                    .iter()
+                    .filter_map(|feature| feature.as_str())
+                    .map(|feature| feature.to_string())
+                    .collect()
+            };
         }
 
-        //parse uses_default_features
+        //parse uses_default_features - may be written as a string like "false"
         if let Some(uses_default) = data.get("default-features") {
-            let uses_default = uses_default
+            entry.uses_default_features = uses_default
                 .as_bool()
+                .or_else(|| uses_default.as_str()?.trim().parse().ok())
                 .ok_or(eyre!("could not parse default-features"))?;
-
-            uses_default_features = uses_default;
         }
 
         //parse rename - package
         if let Some(package) = data.get("package") {
             let package = package.as_str().ok_or(eyre!("could not parse package"))?;
 
-            rename = Some(package.to_string());
+            entry.package = Some(package.to_string());
         }
     } else {
-        version = data.as_str().ok_or(eyre!("could not parse version"))?;
+        entry.version = data
+            .as_str()
+            .ok_or(eyre!("could not parse version"))?
+            .to_string();
+    }
+
+    Ok(entry)
+}
+
+/// the `[features]` of the package itself - the features in `default` are enabled
+pub fn parse_own_features(
+    package: &cargo_metadata::Package,
+) -> color_eyre::Result<Option<Dependency>> {
+    if package.features.keys().all(|name| name == "default") {
+        return Ok(None);
     }
 
     let mut dependency = Dependency {
-        name: name.to_string(),
-        rename,
+        name: package.name.to_string(),
+        rename: None,
         comment: None,
-        version: version.to_string(),
+        // taken from the metadata - `version.workspace = true` is already resolved by cargo
+        version: package.version.to_string(),
+        resolved_version: None,
         workspace: false,
-        kind: DependencyType::Workspace,
+        optional: false,
+        kind: DependencyType::Features,
         target: None,
-        features: Default::default(),
+        features: HashMap::new(),
     };
 
-    if let Ok(package) = get_package_from_version(name, &VersionReq::parse(version)?, packages) {
-        set_features(
-            &mut dependency,
-            package,
-            uses_default_features,
-            &enabled_features,
-        )?;
-    } else {
-        dependency.comment = Some("unused".to_string());
-    }
+    set_features_from_map(&mut dependency, &package.features, true, &vec![])?;
 
-    Ok(dependency)
+    Ok(Some(dependency))
 }
 
 pub fn set_features(
@@ -155,25 +249,46 @@ pub fn set_features(
     uses_default_features: bool,
     enabled_features: &Vec<String>,
 ) -> color_eyre::Result<()> {
-    let default_features = package.features.get("default").cloned().unwrap_or(vec![]);
+    set_features_from_map(
+        dependency,
+        &package.features,
+        uses_default_features,
+        enabled_features,
+    )
+}
+
+/// sets the features of the dependency from a map of feature name to sub features - like the `[features]` table of a manifest
+///
+/// the enabled features are always enabled - the features of `default` only if `uses_default_features` is set.
+/// with `default-features = false` a default feature listed in `features` therefore only enables itself.
+/// enabled features which are unknown to the dependency are kept as custom features
+pub fn set_features_from_map(
+    dependency: &mut Dependency,
+    features: &BTreeMap<String, Vec<String>>,
+    uses_default_features: bool,
+    enabled_features: &Vec<String>,
+) -> color_eyre::Result<()> {
+    let default_features = features.get("default").cloned().unwrap_or(vec![]);
 
-    let features = package
-        .features
+    // cargo lists every optional dependency as `dep:name` - explicitly or via its implicit feature
+    let optional_dependencies = features
+        .values()
+        .flatten()
+        .filter_map(|name| name.strip_prefix("dep:"))
+        .collect_vec();
+
+    let features = features
         .iter()
         .map(|(feature, sub_features)| {
             (
                 feature.to_string(),
                 FeatureData {
-                    sub_features: sub_features
-                        .iter()
-                        .map(|name| SubFeature {
-                            name: name.to_string(),
-                            kind: name.as_str().into(),
-                        })
-                        .filter(|sub_feature| sub_feature.kind != SubFeatureType::DependencyFeature)
-                        .collect_vec(),
+                    sub_features: get_sub_features(sub_features, &optional_dependencies),
                     is_default: default_features.contains(feature),
+                    is_custom: false,
                     enabled_state: EnabledState::Normal(false),
+                    note: None,
+                    conflicts: vec![],
                 },
             )
         })
@@ -183,7 +298,7 @@ pub fn set_features(
 
     for feature in enabled_features {
         if Into::<SubFeatureType>::into(feature.as_str()) == SubFeatureType::Normal {
-            dependency.enable_feature(feature)?;
+            dependency.add_custom_feature(feature)?;
         }
     }
 
@@ -197,3 +312,135 @@ pub fn set_features(
 
     Ok(())
 }
+
+/// `name/feature` also activates the optional dependency `name` - kept as its `dep:name` edge, `name?/feature` does not
+fn get_sub_features(sub_features: &[String], optional_dependencies: &[&str]) -> Vec<SubFeature> {
+    let mut result: Vec<SubFeature> = vec![];
+
+    for name in sub_features {
+        let sub_feature = match SubFeatureType::from(name.as_str()) {
+            SubFeatureType::DependencyFeature => {
+                let Some((dependency, _)) = name.split_once('/') else {
+                    continue;
+                };
+
+                if !optional_dependencies.contains(&dependency) {
+                    continue;
+                }
+
+                SubFeature {
+                    name: format!("dep:{}", dependency),
+                    kind: SubFeatureType::Dependency,
+                }
+            }
+            kind => SubFeature {
+                name: name.to_string(),
+                kind,
+            },
+        };
+
+        if !result
+            .iter()
+            .any(|existing| existing.name == sub_feature.name)
+        {
+            result.push(sub_feature);
+        }
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::{parse_dependency_entry, DependencyEntry};
+    use crate::project::dependency::builder::DependencyBuilder;
+    use crate::project::dependency::Dependency;
+    use toml_edit::DocumentMut;
+
+    const MANIFEST: &str = r#"
+[workspace.dependencies]
+serde = "1"
+tokio = { version = "1.38", features = ["rt", "macros"], default-features = false }
+clap = { version = "4", features = "derive", default-features = "false" }
+"#;
+
+    fn entry(name: &str) -> DependencyEntry {
+        let doc: DocumentMut = MANIFEST.parse().unwrap();
+
+        parse_dependency_entry(&doc["workspace"]["dependencies"][name]).unwrap()
+    }
+
+    fn load(entry: &DependencyEntry) -> Dependency {
+        DependencyBuilder::new("tokio")
+            .feature("default", &["rt"])
+            .feature("rt", &[])
+            .feature("macros", &[])
+            .feature("net", &["io"])
+            .feature("io", &[])
+            .enabled(
+                &entry
+                    .features
+                    .iter()
+                    .map(String::as_str)
+                    .collect::<Vec<_>>(),
+            )
+            .default_features(entry.uses_default_features)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn parses_a_version_string() {
+        assert_eq!(
+            entry("serde"),
+            DependencyEntry {
+                version: "1".to_string(),
+                features: vec![],
+                uses_default_features: true,
+                package: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_features_and_default_features() {
+        assert_eq!(
+            entry("tokio"),
+            DependencyEntry {
+                version: "1.38".to_string(),
+                features: vec!["rt".to_string(), "macros".to_string()],
+                uses_default_features: false,
+                package: None,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_string_forms_of_features_and_default_features() {
+        let entry = entry("clap");
+
+        assert_eq!(entry.features, ["derive"]);
+        assert!(!entry.uses_default_features);
+    }
+
+    #[test]
+    fn loaded_features_are_enabled() {
+        let dependency = load(&entry("tokio"));
+
+        assert!(dependency.get_feature("rt").unwrap().is_enabled());
+        assert!(dependency.get_feature("macros").unwrap().is_enabled());
+        assert!(!dependency.get_feature("net").unwrap().is_enabled());
+        assert!(!dependency.get_feature("default").unwrap().is_enabled());
+        assert_eq!(dependency.get_enabled_features_count(), 2);
+    }
+
+    #[test]
+    fn loaded_default_features_are_enabled() {
+        let dependency = load(&entry("serde"));
+
+        assert!(dependency.get_feature("rt").unwrap().is_enabled());
+        assert_eq!(dependency.get_enabled_features_count(), 1);
+    }
+}
@@ -0,0 +1,299 @@
+use crate::project::package::Package;
+use crate::util::{get_config_string, get_item_from_doc, toml_document_from_path};
+use color_eyre::eyre::{bail, eyre, ContextCompat, WrapErr};
+use color_eyre::Result;
+use std::collections::HashMap;
+use std::path::Path;
+use toml_edit::Item;
+
+/// an entry of `cargo-features-manager.keep`
+///
+/// either a list of features - `clap = ["default"]`
+/// or a table with notes why they are kept - `clap = { keep = ["default"], notes = { default = "..." } }`
+/// and how far prune trusts its checks - `tokio = { prune = "never" }`
+#[derive(Debug, PartialEq)]
+pub struct KeepEntry {
+    pub features: Vec<String>,
+    pub notes: HashMap<String, String>,
+    pub prune: PruneTrust,
+}
+
+/// how far prune trusts a passing check of a dependency - e.g. for features only used at runtime
+///
+/// ordered from the weakest to the strictest
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum PruneTrust {
+    /// a passing check means the feature can be removed
+    #[default]
+    Check,
+    /// the tests are always run - even with `--skip-tests`
+    Test,
+    /// the features are never removed - prune skips the dependency
+    Never,
+}
+
+/// returns the keep entries per dependency name - empty if the manifest or the section does not exist
+pub fn get_keep_entries<P: AsRef<Path>>(
+    dir_path: P,
+    item_path: &str,
+) -> Result<HashMap<String, KeepEntry>> {
+    let Ok(document) = toml_document_from_path(dir_path.as_ref().join("Cargo.toml")) else {
+        return Ok(HashMap::new());
+    };
+
+    let Ok(item) = get_item_from_doc(item_path, &document) else {
+        return Ok(HashMap::new());
+    };
+
+    let table = item.as_table_like().context(format!(
+        "could not parse {} in {:?}",
+        item_path,
+        dir_path.as_ref()
+    ))?;
+
+    table
+        .iter()
+        .map(|(name, item)| {
+            parse_keep_entry(item)
+                .map(|entry| (name.to_string(), entry))
+                .wrap_err_with(|| format!("could not parse {}.{}", item_path, name))
+        })
+        .collect()
+}
+
+impl KeepEntry {
+    /// adds the features & notes of the other entry - existing notes are kept
+    fn merge(&mut self, other: KeepEntry) {
+        for feature in other.features {
+            if !self.features.contains(&feature) {
+                self.features.push(feature);
+            }
+        }
+
+        for (feature, note) in other.notes {
+            self.notes.entry(feature).or_insert(note);
+        }
+
+        self.prune = self.prune.max(other.prune);
+    }
+}
+
+pub fn parse_keep_entry(item: &Item) -> Result<KeepEntry> {
+    if let Some(features) = item.as_array() {
+        return Ok(KeepEntry {
+            features: parse_string_array(features)?,
+            notes: HashMap::new(),
+            prune: PruneTrust::default(),
+        });
+    }
+
+    let table = item
+        .as_table_like()
+        .ok_or(eyre!("Invalid format to keep features"))?;
+
+    let features = match table.get("keep") {
+        None => vec![],
+        Some(keep) => parse_string_array(
+            keep.as_array()
+                .ok_or(eyre!("could not parse keep - not an array"))?,
+        )?,
+    };
+
+    let mut notes = HashMap::new();
+
+    if let Some(notes_item) = table.get("notes") {
+        let notes_table = notes_item
+            .as_table_like()
+            .ok_or(eyre!("could not parse notes - not a table"))?;
+
+        for (feature, note) in notes_table.iter() {
+            let note = note.as_str().ok_or(eyre!(
+                "could not parse the note of {} - not a string",
+                feature
+            ))?;
+
+            notes.insert(feature.to_string(), note.to_string());
+        }
+    }
+
+    let prune = match table.get("prune") {
+        None => PruneTrust::default(),
+        Some(prune) => match prune.as_str() {
+            Some("check") => PruneTrust::Check,
+            Some("test") => PruneTrust::Test,
+            Some("never") => PruneTrust::Never,
+            _ => bail!("could not parse prune - expected \"check\", \"test\" or \"never\""),
+        },
+    };
+
+    Ok(KeepEntry {
+        features,
+        notes,
+        prune,
+    })
+}
+
+fn parse_string_array(array: &toml_edit::Array) -> Result<Vec<String>> {
+    array
+        .iter()
+        .map(|value| {
+            value
+                .as_str()
+                .map(|value| value.to_string())
+                .ok_or(eyre!("could not parse keep - a feature is not a string"))
+        })
+        .collect()
+}
+
+/// returns the entries of `workspace.cargo-features-manager.keep` in the root manifest
+///
+/// entries of the file configured via `keep-file` (relative to the root) are added to them
+pub fn get_workspace_keep_entries(root_path: &str) -> Result<HashMap<String, KeepEntry>> {
+    let mut entries = get_keep_entries(root_path, "workspace.cargo-features-manager.keep")?;
+
+    let Some(keep_file) = get_config_string(root_path, "keep-file")? else {
+        return Ok(entries);
+    };
+
+    let keep_file = Path::new(root_path).join(keep_file);
+
+    let document = toml_document_from_path(&keep_file)
+        .wrap_err_with(|| format!("could not read the keep-file {:?}", keep_file))?;
+
+    for (name, item) in document.iter() {
+        let file_entry = parse_keep_entry(item)
+            .wrap_err_with(|| format!("could not parse {} in {:?}", name, keep_file))?;
+
+        match entries.get_mut(name) {
+            Some(entry) => entry.merge(file_entry),
+            None => {
+                entries.insert(name.to_string(), file_entry);
+            }
+        }
+    }
+
+    Ok(entries)
+}
+
+/// attaches the notes of the keep entries to the features of the dependencies - the notes of the package win over the ones of the workspace
+pub fn apply_feature_notes(package: &mut Package, workspace_entries: &HashMap<String, KeepEntry>) {
+    for dependency in &mut package.dependencies {
+        if dependency.is_own_features() {
+            continue;
+        }
+
+        for entries in [workspace_entries, &package.keep] {
+            let Some(entry) = entries.get(&dependency.name) else {
+                continue;
+            };
+
+            for (feature, note) in &entry.notes {
+                if let Some(data) = dependency.features.get_mut(feature) {
+                    data.note = Some(note.to_string());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::{parse_keep_entry, KeepEntry, PruneTrust};
+    use std::collections::HashMap;
+    use toml_edit::DocumentMut;
+
+    const MANIFEST: &str = r#"
+[cargo-features-manager.keep]
+clap = ["default"]
+tokio = { keep = ["rt"], notes = { rt = "needed for the async runtime" } }
+serde = { notes = { derive = "used by the config" } }
+rustls = { keep = ["ring"], prune = "test" }
+"#;
+
+    fn entry(name: &str) -> KeepEntry {
+        let doc: DocumentMut = MANIFEST.parse().unwrap();
+
+        parse_keep_entry(&doc["cargo-features-manager"]["keep"][name]).unwrap()
+    }
+
+    #[test]
+    fn parses_a_list_of_features() {
+        assert_eq!(
+            entry("clap"),
+            KeepEntry {
+                features: vec!["default".to_string()],
+                notes: HashMap::new(),
+                prune: PruneTrust::Check,
+            }
+        );
+    }
+
+    #[test]
+    fn parses_features_with_notes() {
+        let entry = entry("tokio");
+
+        assert_eq!(entry.features, ["rt"]);
+        assert_eq!(entry.notes["rt"], "needed for the async runtime");
+    }
+
+    #[test]
+    fn parses_notes_without_kept_features() {
+        let entry = entry("serde");
+
+        assert!(entry.features.is_empty());
+        assert_eq!(entry.notes["derive"], "used by the config");
+    }
+
+    #[test]
+    fn merging_keeps_existing_notes() {
+        let mut entry = entry("tokio");
+
+        entry.merge(KeepEntry {
+            features: vec!["rt".to_string(), "net".to_string()],
+            notes: HashMap::from([
+                ("rt".to_string(), "other".to_string()),
+                ("net".to_string(), "used by the server".to_string()),
+            ]),
+            prune: PruneTrust::Never,
+        });
+
+        assert_eq!(entry.features, ["rt", "net"]);
+        assert_eq!(entry.notes["rt"], "needed for the async runtime");
+        assert_eq!(entry.notes["net"], "used by the server");
+        assert_eq!(entry.prune, PruneTrust::Never);
+    }
+
+    #[test]
+    fn parses_how_far_prune_is_trusted() {
+        let entry = entry("rustls");
+
+        assert_eq!(entry.features, ["ring"]);
+        assert_eq!(entry.prune, PruneTrust::Test);
+
+        let doc: DocumentMut = "a = { prune = \"never\" }\nb = { prune = \"always\" }"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            parse_keep_entry(&doc["a"]).unwrap().prune,
+            PruneTrust::Never
+        );
+        assert!(parse_keep_entry(&doc["b"]).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_entries() {
+        let doc: DocumentMut =
+            "a = 1\nb = { keep = \"rt\" }\nc = { notes = [\"rt\"] }\nd = [\"rt\", 1]\ne = { keep = [true] }"
+                .parse()
+                .unwrap();
+
+        assert!(parse_keep_entry(&doc["a"]).is_err());
+        assert!(parse_keep_entry(&doc["b"]).is_err());
+        assert!(parse_keep_entry(&doc["c"]).is_err());
+        assert!(parse_keep_entry(&doc["d"]).is_err());
+        assert!(parse_keep_entry(&doc["e"]).is_err());
+    }
+}
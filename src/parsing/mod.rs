@@ -1,3 +1,6 @@
+pub mod conflicts;
 pub mod dependency;
+pub mod keep;
 pub mod package;
+pub mod transitive;
 pub mod workspace;
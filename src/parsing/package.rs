@@ -1,20 +1,65 @@
 use cargo_metadata::{CargoOpt, PackageId};
+use console::Term;
 
 use crate::parsing::workspace::parse_workspace;
-use color_eyre::Result;
+use color_eyre::{Report, Result, Section, SectionExt};
 
-use crate::parsing::dependency::parse_dependency;
+use crate::parsing::conflicts::{apply_conflicts, get_conflicts};
+use crate::parsing::dependency::{parse_dependency, parse_own_features};
+use crate::parsing::keep::{
+    apply_feature_notes, get_keep_entries, get_workspace_keep_entries, KeepEntry,
+};
 use crate::project::dependency::Dependency;
 use crate::project::package::Package;
 use crate::util::toml_document_from_path;
-use color_eyre::eyre::ContextCompat;
+use color_eyre::eyre::{eyre, ContextCompat};
 use semver::VersionReq;
 use std::collections::HashMap;
+use std::io::Write;
+use std::path::Path;
+use std::time::Instant;
+use tracing::{debug, warn};
 
-pub fn get_packages() -> Result<(Vec<Package>, Option<Package>)> {
-    let metadata = cargo_metadata::MetadataCommand::new()
-        .features(CargoOpt::AllFeatures)
-        .exec()?;
+/// the packages of the project loaded via cargo metadata
+pub struct Project {
+    pub packages: Vec<Package>,
+    pub workspace: Option<Package>,
+    /// the directory of the root manifest - configuration is read from there
+    pub root_path: String,
+    /// the entries of `workspace.cargo-features-manager.keep` in the root manifest
+    pub workspace_keep: HashMap<String, KeepEntry>,
+    /// the dependencies that could not be parsed - they are left out of their package
+    pub errors: Vec<String>,
+}
+
+/// `frozen` forbids cargo to access the network or update the Cargo.lock
+///
+/// without a manifest path cargo looks for the project in the current directory
+///
+/// `progress` prints the loading progress to stderr - only if it is a terminal
+pub fn get_packages(manifest_path: Option<&Path>, frozen: bool, progress: bool) -> Result<Project> {
+    let mut term = Term::stderr();
+    let show_progress = progress && term.is_term();
+
+    if show_progress {
+        term.write_str("reading the project via cargo metadata...")?;
+    }
+
+    let mut command = metadata_command(manifest_path, frozen);
+    command.features(CargoOpt::AllFeatures);
+
+    debug!("running {:?}", command.cargo_command());
+    let start = Instant::now();
+
+    let metadata = command.exec().map_err(metadata_error);
+
+    debug!("cargo metadata finished after {:.1?}", start.elapsed());
+
+    if show_progress {
+        term.clear_line()?;
+    }
+
+    let metadata = metadata?;
 
     let metadata_packages: HashMap<PackageId, cargo_metadata::Package> = metadata
         .packages
@@ -22,36 +67,189 @@ pub fn get_packages() -> Result<(Vec<Package>, Option<Package>)> {
         .map(|package| (package.id.clone(), package))
         .collect();
 
-    let packages = metadata
-        .workspace_members
-        .iter()
-        .map(|package| parse_package(package, &metadata_packages))
-        .collect::<Result<Vec<Package>>>()?;
+    let mut errors = vec![];
+    let mut packages = vec![];
+
+    for (index, package) in metadata.workspace_members.iter().enumerate() {
+        if show_progress {
+            term.clear_line()?;
+            write!(
+                term,
+                "loading packages [{}/{}]",
+                index + 1,
+                metadata.workspace_members.len()
+            )?;
+        }
+
+        packages.push(parse_package(package, &metadata_packages, &mut errors)?);
+    }
 
-    Ok((
+    if show_progress {
+        term.clear_line()?;
+    }
+
+    let root_path = metadata.workspace_root.to_string();
+    let workspace_keep = get_workspace_keep_entries(&root_path)?;
+
+    let workspace_conflicts =
+        get_conflicts(&root_path, "workspace.cargo-features-manager.conflicts")?;
+
+    for package in &mut packages {
+        apply_feature_notes(package, &workspace_keep);
+
+        let package_conflicts = get_conflicts(
+            Path::new(&package.manifest_path)
+                .parent()
+                .unwrap_or(Path::new(".")),
+            "cargo-features-manager.conflicts",
+        )?;
+
+        apply_conflicts(package, &workspace_conflicts);
+        apply_conflicts(package, &package_conflicts);
+    }
+
+    debug!(
+        "loaded {} packages with {} dependencies",
+        packages.len(),
+        packages
+            .iter()
+            .map(|package| package.dependencies.len())
+            .sum::<usize>()
+    );
+
+    let mut workspace = parse_workspace(&root_path, &metadata_packages)?;
+
+    if let Some(workspace) = &mut workspace {
+        apply_conflicts(workspace, &workspace_conflicts);
+    }
+
+    Ok(Project {
         packages,
-        parse_workspace(metadata.workspace_root.as_str(), &metadata_packages)?,
-    ))
+        workspace,
+        root_path,
+        workspace_keep,
+        errors,
+    })
+}
+
+/// the dependency graph resolved with the default features of the workspace members - like a plain `cargo build`
+pub fn get_resolved_metadata(frozen: bool) -> Result<cargo_metadata::Metadata> {
+    metadata_command(None, frozen)
+        .exec()
+        .map_err(metadata_error)
 }
 
+fn metadata_command(manifest_path: Option<&Path>, frozen: bool) -> cargo_metadata::MetadataCommand {
+    let mut command = cargo_metadata::MetadataCommand::new();
+
+    if let Some(manifest_path) = manifest_path {
+        command.manifest_path(manifest_path);
+    }
+
+    if frozen {
+        command.other_options(vec!["--frozen".to_string()]);
+    }
+
+    command
+}
+
+/// turns the error of cargo metadata into an actionable one - e.g. when the registry index is missing or outdated
+fn metadata_error(error: cargo_metadata::Error) -> Report {
+    let cargo_metadata::Error::CargoMetadata { stderr } = &error else {
+        return Report::new(error).wrap_err("could not read the project via cargo metadata");
+    };
+
+    if let Some(name) = get_missing_crate(stderr) {
+        return eyre!(
+            "the crate {} could not be found in the registry index",
+            name
+        )
+        .with_section(|| stderr.trim().to_string().header("cargo:"))
+        .suggestion("check the name of the dependency or run `cargo update` to update the index");
+    }
+
+    let is_registry_error = is_registry_error(stderr);
+    let report = Report::new(error).wrap_err("could not read the project via cargo metadata");
+
+    if is_registry_error {
+        report.suggestion(
+            "the registry index could not be read - run `cargo fetch` with network access first",
+        )
+    } else {
+        report
+    }
+}
+
+/// returns the name of the crate cargo could not find in the index - e.g. "no matching package named `foo` found"
+fn get_missing_crate(stderr: &str) -> Option<&str> {
+    let (_, rest) = stderr.split_once("no matching package named `")?;
+    let (name, _) = rest.split_once('`')?;
+
+    Some(name)
+}
+
+fn is_registry_error(stderr: &str) -> bool {
+    [
+        "failed to get",
+        "failed to download",
+        "failed to load source",
+        "failed to update registry",
+        "unable to update registry",
+        "failed to query replaced source registry",
+        "attempting to make an http request, but --offline was specified",
+    ]
+    .iter()
+    .any(|message| stderr.to_lowercase().contains(message))
+}
+
+/// a dependency that can not be parsed is left out & its error added to `errors` - the package is still loaded
 pub fn parse_package(
     package: &PackageId,
     packages: &HashMap<PackageId, cargo_metadata::Package>,
+    errors: &mut Vec<String>,
 ) -> Result<Package> {
     let package = packages.get(package).context("package not found")?;
 
     let toml_doc = toml_document_from_path(package.manifest_path.as_str())?;
 
-    let dependencies: Result<Vec<Dependency>> = package
+    let mut dependencies: Vec<Dependency> = package
         .dependencies
         .iter()
-        .map(|dep| parse_dependency(dep, packages, &toml_doc))
+        .filter_map(|dep| match parse_dependency(dep, packages, &toml_doc) {
+            Ok(dependency) => Some(dependency),
+            Err(err) => {
+                warn!(
+                    "could not parse dependency {} of {}: {:?}",
+                    dep.name, package.name, err
+                );
+
+                errors.push(format!(
+                    "could not parse dependency {} of {} - {:#}",
+                    dep.name, package.name, err
+                ));
+                None
+            }
+        })
         .collect();
 
+    if let Some(own_features) = parse_own_features(package)? {
+        dependencies.insert(0, own_features);
+    }
+
+    let keep = get_keep_entries(
+        package
+            .manifest_path
+            .parent()
+            .map(|path| path.as_str())
+            .unwrap_or("."),
+        "cargo-features-manager.keep",
+    )?;
+
     Ok(Package {
-        dependencies: dependencies?,
+        dependencies,
         name: package.name.to_string(),
         manifest_path: package.manifest_path.to_string(),
+        keep,
     })
 }
 
@@ -61,8 +259,7 @@ pub fn get_package_from_version<'a>(
     packages: &'a HashMap<PackageId, cargo_metadata::Package>,
 ) -> Result<&'a cargo_metadata::Package> {
     packages
-        .iter()
-        .map(|(_, package)| package)
+        .values()
         .filter(|package| package.name == name)
         .find(|package| version_req.matches(&package.version) || version_req.to_string() == "*")
         .context(format!(
@@ -70,3 +267,27 @@ pub fn get_package_from_version<'a>(
             name, version_req
         ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{get_missing_crate, is_registry_error};
+
+    #[test]
+    fn finds_the_name_of_a_missing_crate() {
+        let stderr = "error: no matching package named `serde_jsn` found\nlocation searched: crates.io index\n";
+
+        assert_eq!(get_missing_crate(stderr), Some("serde_jsn"));
+        assert_eq!(get_missing_crate("error: failed to parse manifest"), None);
+    }
+
+    #[test]
+    fn detects_registry_errors() {
+        assert!(is_registry_error(
+            "error: failed to get `serde` as a dependency of package `foo v0.1.0`"
+        ));
+        assert!(is_registry_error(
+            "error: attempting to make an HTTP request, but --offline was specified"
+        ));
+        assert!(!is_registry_error("error: failed to parse manifest"));
+    }
+}
@@ -0,0 +1,116 @@
+use cargo_metadata::{Metadata, PackageId};
+use itertools::Itertools;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// a dependency pulled in by another dependency - not declared in the manifest of the package
+pub struct TransitiveDependency {
+    pub name: String,
+    pub version: String,
+}
+
+/// returns the transitive dependencies of the package in the resolved graph - sorted by name, only the newest version of each
+///
+/// direct dependencies & workspace members are skipped - empty if the package is not part of the graph (e.g. a virtual workspace)
+pub fn get_transitive_dependencies(
+    metadata: &Metadata,
+    package_name: &str,
+) -> Vec<TransitiveDependency> {
+    let Some(resolve) = &metadata.resolve else {
+        return vec![];
+    };
+
+    let Some(package) = metadata.packages.iter().find(|package| {
+        package.name == package_name && metadata.workspace_members.contains(&package.id)
+    }) else {
+        return vec![];
+    };
+
+    let graph: HashMap<&PackageId, Vec<&PackageId>> = resolve
+        .nodes
+        .iter()
+        .map(|node| (&node.id, node.dependencies.iter().collect()))
+        .collect();
+
+    let direct: HashSet<&str> = package
+        .dependencies
+        .iter()
+        .map(|dependency| dependency.name.as_str())
+        .collect();
+
+    let reachable = get_reachable(&graph, &package.id);
+
+    metadata
+        .packages
+        .iter()
+        .filter(|other| reachable.contains(&other.id))
+        .filter(|other| !metadata.workspace_members.contains(&other.id))
+        .filter(|other| !direct.contains(other.name.as_str()))
+        .sorted_by(|a, b| a.name.cmp(&b.name).then(b.version.cmp(&a.version)))
+        .dedup_by(|a, b| a.name == b.name)
+        .map(|other| TransitiveDependency {
+            name: other.name.to_string(),
+            version: other.version.to_string(),
+        })
+        .collect()
+}
+
+/// every package reachable from the start - not including the start itself
+fn get_reachable<'a>(
+    graph: &HashMap<&'a PackageId, Vec<&'a PackageId>>,
+    start: &'a PackageId,
+) -> HashSet<&'a PackageId> {
+    let mut reachable = HashSet::new();
+    let mut queue = VecDeque::from([start]);
+
+    while let Some(id) = queue.pop_front() {
+        for dependency in graph.get(id).into_iter().flatten() {
+            if *dependency != start && reachable.insert(*dependency) {
+                queue.push_back(*dependency);
+            }
+        }
+    }
+
+    reachable
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::get_reachable;
+    use cargo_metadata::PackageId;
+    use itertools::Itertools;
+    use std::collections::HashMap;
+
+    fn id(repr: &str) -> PackageId {
+        PackageId {
+            repr: repr.to_string(),
+        }
+    }
+
+    #[test]
+    fn reaches_every_package_once() {
+        let (app, clap, clap_builder, anstyle, strsim) = (
+            id("app"),
+            id("clap"),
+            id("clap_builder"),
+            id("anstyle"),
+            id("strsim"),
+        );
+
+        let graph = HashMap::from([
+            (&app, vec![&clap, &strsim]),
+            (&clap, vec![&clap_builder]),
+            (&clap_builder, vec![&anstyle, &strsim]),
+            (&anstyle, vec![&app]),
+        ]);
+
+        let reachable = get_reachable(&graph, &app)
+            .into_iter()
+            .map(|id| id.repr.as_str())
+            .sorted()
+            .collect_vec();
+
+        assert_eq!(reachable, ["anstyle", "clap", "clap_builder", "strsim"]);
+    }
+}
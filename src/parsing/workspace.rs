@@ -3,7 +3,7 @@ use crate::project::dependency::Dependency;
 use crate::project::package::Package;
 use crate::util::toml_document_from_path;
 use cargo_metadata::PackageId;
-use color_eyre::eyre::eyre;
+use color_eyre::eyre::{eyre, WrapErr};
 use color_eyre::Result;
 use console::Emoji;
 use std::collections::HashMap;
@@ -29,13 +29,17 @@ pub fn parse_workspace(
 
     let dependencies: Result<Vec<Dependency>> = dependencies_table
         .iter()
-        .map(|(name, data)| parse_dependency_from_item(packages, name, data))
+        .map(|(name, data)| {
+            parse_dependency_from_item(packages, name, data)
+                .wrap_err_with(|| format!("could not parse workspace dependency {}", name))
+        })
         .collect();
 
     let package = Package {
         dependencies: dependencies?,
         name: format!("{} Workspace", Emoji("🗃️", "")).to_string(),
         manifest_path: path,
+        keep: HashMap::new(),
     };
 
     Ok(Some(package))
@@ -0,0 +1,102 @@
+use crate::parsing::conflicts::apply_conflict_groups;
+use crate::parsing::dependency::set_features_from_map;
+use crate::project::dependency::{Dependency, DependencyType};
+use std::collections::{BTreeMap, HashMap};
+
+/// builds a dependency from an in-memory feature map - without requiring cargo metadata
+pub struct DependencyBuilder {
+    name: String,
+    version: String,
+    kind: DependencyType,
+
+    features: BTreeMap<String, Vec<String>>,
+    enabled_features: Vec<String>,
+    uses_default_features: bool,
+    workspace: bool,
+    conflicts: Vec<Vec<String>>,
+}
+
+impl DependencyBuilder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            version: "1.0.0".to_string(),
+            kind: DependencyType::Normal,
+            features: BTreeMap::new(),
+            enabled_features: vec![],
+            uses_default_features: true,
+            workspace: false,
+            conflicts: vec![],
+        }
+    }
+
+    pub fn version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    pub fn kind(mut self, kind: DependencyType) -> Self {
+        self.kind = kind;
+        self
+    }
+
+    /// adds a feature with its sub features - e.g. `feature("full", &["rt", "dep:foo"])`
+    pub fn feature(mut self, name: &str, sub_features: &[&str]) -> Self {
+        self.features.insert(
+            name.to_string(),
+            sub_features.iter().map(|name| name.to_string()).collect(),
+        );
+        self
+    }
+
+    /// marks the features as enabled in the manifest
+    pub fn enabled(mut self, features: &[&str]) -> Self {
+        self.enabled_features
+            .extend(features.iter().map(|name| name.to_string()));
+        self
+    }
+
+    pub fn default_features(mut self, uses_default_features: bool) -> Self {
+        self.uses_default_features = uses_default_features;
+        self
+    }
+
+    /// declares the features as mutually exclusive - like `cargo-features-manager.conflicts`
+    pub fn conflict(mut self, features: &[&str]) -> Self {
+        self.conflicts
+            .push(features.iter().map(|name| name.to_string()).collect());
+        self
+    }
+
+    /// marks the dependency as inherited from the workspace - `workspace = true`
+    pub fn workspace(mut self) -> Self {
+        self.workspace = true;
+        self
+    }
+
+    pub fn build(self) -> color_eyre::Result<Dependency> {
+        let mut dependency = Dependency {
+            name: self.name,
+            rename: None,
+            comment: None,
+            version: self.version,
+            resolved_version: None,
+            workspace: self.workspace,
+            optional: false,
+            kind: self.kind,
+            target: None,
+            features: HashMap::new(),
+        };
+
+        set_features_from_map(
+            &mut dependency,
+            &self.features,
+            self.uses_default_features,
+            &self.enabled_features,
+        )?;
+
+        apply_conflict_groups(&mut dependency, &self.conflicts);
+
+        Ok(dependency)
+    }
+}
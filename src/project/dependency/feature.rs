@@ -2,10 +2,17 @@ use console::Emoji;
 use std::fmt::{Display, Formatter};
 
 #[derive(Clone, Debug)]
+#[non_exhaustive]
 pub struct FeatureData {
     pub sub_features: Vec<SubFeature>,
     pub is_default: bool,
+    /// the feature is not known to the dependency - e.g. it was added by hand for a newer version
+    pub is_custom: bool,
     pub enabled_state: EnabledState,
+    /// why the feature is kept - from the notes in `cargo-features-manager.keep`
+    pub note: Option<String>,
+    /// features which can not be enabled together with this one - from `cargo-features-manager.conflicts`
+    pub conflicts: Vec<String>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -15,6 +22,17 @@ pub enum EnabledState {
 }
 
 impl FeatureData {
+    pub fn custom() -> Self {
+        Self {
+            sub_features: vec![],
+            is_default: false,
+            is_custom: true,
+            enabled_state: EnabledState::Normal(true),
+            note: None,
+            conflicts: vec![],
+        }
+    }
+
     pub fn is_enabled(&self) -> bool {
         match self.enabled_state {
             EnabledState::Normal(is_enabled) => is_enabled,
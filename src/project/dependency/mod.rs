@@ -1,5 +1,5 @@
 use color_eyre::eyre::{eyre, ContextCompat, Result};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use cargo_platform::Platform;
 
@@ -8,17 +8,26 @@ use cargo_metadata::DependencyKind;
 use console::{style, Emoji};
 use itertools::Itertools;
 
+#[cfg(test)]
+pub mod builder;
 pub mod feature;
+#[cfg(test)]
+mod tests;
 pub mod util;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub struct Dependency {
     pub name: String,
     pub rename: Option<String>,
     pub comment: Option<String>,
+    /// the version requirement - e.g. `1.0`
     pub version: String,
+    /// the concrete version cargo resolved the requirement to - e.g. `1.0.195`
+    pub resolved_version: Option<String>,
 
     pub workspace: bool,
+    pub optional: bool,
     pub kind: DependencyType,
     pub target: Option<Platform>,
 
@@ -53,6 +62,12 @@ impl Dependency {
                 name
             )
             .to_string(),
+            DependencyType::Features => format!(
+                "{} {}",
+                Emoji("🧩", &style("features").color256(8).to_string()),
+                name
+            )
+            .to_string(),
         };
 
         if self.workspace {
@@ -62,10 +77,26 @@ impl Dependency {
         name
     }
 
+    /// returns whether this represents the `[features]` of the package itself - enabled features are the ones in `default`
+    pub fn is_own_features(&self) -> bool {
+        matches!(self.kind, DependencyType::Features)
+    }
+
+    pub fn can_be_optional(&self) -> bool {
+        matches!(self.kind, DependencyType::Normal | DependencyType::Build)
+    }
+
     pub fn get_version(&self) -> String {
         self.version.to_string()
     }
 
+    /// the docs of the version in the Cargo.lock - the requirement (e.g. `>=1, <3`) is no version docs.rs knows
+    pub fn get_docs_url(&self) -> String {
+        let version = self.resolved_version.as_deref().unwrap_or("latest");
+
+        format!("https://docs.rs/{}/{}", self.name, version)
+    }
+
     pub fn get_feature(&self, feature_name: &str) -> Option<&FeatureData> {
         self.features.get(feature_name)
     }
@@ -74,8 +105,125 @@ impl Dependency {
         !self.features.is_empty()
     }
 
+    /// returns the amount of features - excluding `default`
+    pub fn get_features_count(&self) -> usize {
+        self.features
+            .keys()
+            .filter(|name| *name != "default")
+            .count()
+    }
+
+    /// returns the amount of enabled features - excluding `default`
+    pub fn get_enabled_features_count(&self) -> usize {
+        self.features
+            .iter()
+            .filter(|(name, data)| *name != "default" && data.is_enabled())
+            .count()
+    }
+
+    /// returns the pairs of enabled features declared as conflicting - each pair once
+    pub fn get_enabled_conflicts(&self) -> Vec<(String, String)> {
+        self.features
+            .iter()
+            .filter(|(_, data)| data.is_enabled())
+            .flat_map(|(name, data)| {
+                data.conflicts
+                    .iter()
+                    .filter(|other| {
+                        *other > name
+                            && self
+                                .get_feature(other)
+                                .map_or(false, |data| data.is_enabled())
+                    })
+                    .map(move |other| (name.to_string(), other.to_string()))
+            })
+            .sorted()
+            .collect()
+    }
+
+    /// returns the enabled features on top of the defaults & the disabled default features - both sorted
+    ///
+    /// added features enabled by another added feature are left out - e.g. only `derive`, not the `serde_derive` it enables
+    pub fn get_default_delta(&self) -> (Vec<String>, Vec<String>) {
+        let mut defaults = HashSet::new();
+
+        for (name, data) in &self.features {
+            if data.is_default {
+                self.add_enabled_closure(name, &mut defaults);
+            }
+        }
+
+        let added = self
+            .features
+            .iter()
+            .filter(|(name, data)| {
+                *name != "default" && !defaults.contains(*name) && data.is_enabled()
+            })
+            .map(|(name, _)| name)
+            .collect::<HashSet<_>>();
+
+        let mut implied = HashSet::new();
+
+        for name in &added {
+            if let Some(data) = self.features.get(*name) {
+                for sub_feature in &data.sub_features {
+                    if sub_feature.kind == SubFeatureType::Normal {
+                        self.add_enabled_closure(&sub_feature.name, &mut implied);
+                    }
+                }
+            }
+        }
+
+        let added = added
+            .into_iter()
+            .filter(|name| !implied.contains(*name))
+            .map(|name| name.to_string())
+            .sorted()
+            .collect();
+
+        let removed = self
+            .features
+            .iter()
+            .filter(|(name, data)| {
+                *name != "default" && defaults.contains(*name) && !data.is_enabled()
+            })
+            .map(|(name, _)| name.to_string())
+            .sorted()
+            .collect();
+
+        (added, removed)
+    }
+
+    /// returns whether the feature only exists to activate the optional dependency of the same name
+    pub fn is_optional_dependency(&self, feature_name: &str) -> bool {
+        let Some(data) = self.get_feature(feature_name) else {
+            return false;
+        };
+
+        match data.sub_features.as_slice() {
+            [sub_feature] => {
+                sub_feature.kind == SubFeatureType::Dependency
+                    && sub_feature.name.trim_start_matches("dep:") == feature_name
+            }
+            _ => false,
+        }
+    }
+
+    /// returns all optional dependencies which are activated by the currently enabled features
+    pub fn get_enabled_optional_dependencies(&self) -> Vec<String> {
+        self.get_enabled_closure()
+            .iter()
+            .filter_map(|name| self.features.get(name))
+            .flat_map(|data| &data.sub_features)
+            .filter(|sub_feature| sub_feature.kind == SubFeatureType::Dependency)
+            .map(|sub_feature| sub_feature.name.trim_start_matches("dep:").to_string())
+            .sorted()
+            .dedup()
+            .collect()
+    }
+
     pub fn can_use_default(&self) -> bool {
-        if self.workspace {
+        if self.workspace || self.is_own_features() {
             return false;
         }
 
@@ -88,21 +236,107 @@ impl Dependency {
         true
     }
 
+    /// returns the minimal set of features which have to be written to the manifest to enable all currently enabled features
     pub fn get_features_to_enable(&self) -> Vec<String> {
         let can_use_default = self.can_use_default();
 
-        self.features
+        // features which are already enabled by default or by the workspace
+        let mut covered = HashSet::new();
+
+        for (name, data) in &self.features {
+            if (can_use_default && data.is_default) || data.enabled_state == EnabledState::Workspace
+            {
+                self.add_enabled_closure(name, &mut covered);
+            }
+        }
+
+        let required = self.get_features_required_by_enabled();
+
+        // top level features first - features which are only enabled by each other (a cycle) are handled last
+        let (top_level, nested): (Vec<_>, Vec<_>) = self
+            .features
             .iter()
-            .filter(|(_, data)| data.is_enabled())
-            .filter(|(_, data)| !can_use_default || !data.is_default)
-            .filter(|(_, data)| data.enabled_state != EnabledState::Workspace)
-            .map(|(name, _)| name.clone())
-            .filter(|name| name != "default")
-            .filter(|name| self.get_currently_dependent_features(name).is_empty())
+            .filter(|(name, data)| data.is_enabled() && *name != "default")
+            .map(|(name, _)| name)
             .sorted()
+            .partition(|name| !required.contains(name.as_str()));
+
+        let mut features = vec![];
+
+        for name in top_level.into_iter().chain(nested) {
+            if covered.contains(name) {
+                continue;
+            }
+
+            self.add_enabled_closure(name, &mut covered);
+            features.push(name.to_string());
+        }
+
+        features.sort();
+        features
+    }
+
+    /// returns whether the feature is not enabled itself but required by an enabled feature - only happens when toggling explicitly
+    pub fn is_implied(&self, feature_name: &str) -> bool {
+        match self.get_feature(feature_name) {
+            Some(data) if !data.is_enabled() => self.get_enabled_closure().contains(feature_name),
+            _ => false,
+        }
+    }
+
+    /// returns the features which are not enabled themselves but required by an enabled feature - `is_implied` for all features at once
+    pub fn get_implied_features(&self) -> HashSet<String> {
+        let mut closure = self.get_enabled_closure();
+
+        closure.retain(|name| {
+            self.get_feature(name)
+                .map_or(false, |data| !data.is_enabled())
+        });
+
+        closure
+    }
+
+    /// returns the features which are a direct sub feature of an enabled feature - `get_currently_dependent_features` for all features at once
+    pub fn get_features_required_by_enabled(&self) -> HashSet<&str> {
+        self.features
+            .values()
+            .filter(|data| data.is_enabled())
+            .flat_map(|data| &data.sub_features)
+            .filter(|sub_feature| self.features.contains_key(&sub_feature.name))
+            .map(|sub_feature| sub_feature.name.as_str())
             .collect()
     }
 
+    /// returns the enabled features and all sub features they enable
+    fn get_enabled_closure(&self) -> HashSet<String> {
+        let mut closure = HashSet::new();
+
+        for (name, data) in &self.features {
+            if data.is_enabled() {
+                self.add_enabled_closure(name, &mut closure);
+            }
+        }
+
+        closure
+    }
+
+    /// adds the feature and all sub features it enables
+    fn add_enabled_closure(&self, feature_name: &str, closure: &mut HashSet<String>) {
+        if !closure.insert(feature_name.to_string()) {
+            return;
+        }
+
+        let Some(data) = self.features.get(feature_name) else {
+            return;
+        };
+
+        for sub_feature in &data.sub_features {
+            if sub_feature.kind == SubFeatureType::Normal {
+                self.add_enabled_closure(&sub_feature.name, closure);
+            }
+        }
+    }
+
     pub fn toggle_feature(&mut self, feature_name: &str) -> Result<()> {
         let data = self
             .features
@@ -120,6 +354,81 @@ impl Dependency {
         Ok(())
     }
 
+    /// toggles only the feature itself - its sub features are implied by cargo and features requiring it stay enabled
+    pub fn toggle_feature_explicit(&mut self, feature_name: &str) -> Result<()> {
+        let data = self
+            .features
+            .get_mut(feature_name)
+            .context(format!("could not find {}", feature_name))?;
+
+        if let EnabledState::Normal(is_enabled) = data.enabled_state {
+            data.enabled_state = EnabledState::Normal(!is_enabled);
+        }
+
+        Ok(())
+    }
+
+    /// returns the names of all features which are enabled by the dependency itself - excluding the workspace
+    pub fn get_own_enabled_features(&self) -> Vec<String> {
+        self.features
+            .iter()
+            .filter(|(_, data)| data.enabled_state == EnabledState::Normal(true))
+            .map(|(name, _)| name.to_string())
+            .sorted()
+            .collect()
+    }
+
+    /// enables exactly the given features - features the dependency does not have are ignored
+    pub fn set_enabled_features(&mut self, feature_names: &[String]) -> Result<()> {
+        for data in self.features.values_mut() {
+            if data.is_toggleable() {
+                data.enabled_state = EnabledState::Normal(false);
+            }
+        }
+
+        for name in feature_names {
+            if self.features.contains_key(name) {
+                self.enable_feature(name)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// enables every feature of the dependency
+    pub fn enable_all_features(&mut self) -> Result<()> {
+        let names = self.features.keys().cloned().collect_vec();
+
+        for name in names {
+            self.enable_feature(&name)?;
+        }
+
+        Ok(())
+    }
+
+    /// disables the default features - features enabled on top of them stay enabled
+    pub fn disable_default_features(&mut self) -> Result<()> {
+        let features = self.get_features_to_enable();
+
+        self.set_enabled_features(&features)
+    }
+
+    /// enables the feature - if the dependency does not know the feature it is added as a custom feature
+    ///
+    /// returns whether the feature was unknown
+    pub fn add_custom_feature(&mut self, feature_name: &str) -> Result<bool> {
+        if self.features.contains_key(feature_name) {
+            self.enable_feature(feature_name)?;
+
+            return Ok(false);
+        }
+
+        self.features
+            .insert(feature_name.to_string(), FeatureData::custom());
+
+        Ok(true)
+    }
+
     pub fn set_feature_to_workspace(&mut self, feature_name: &str) -> Result<()> {
         let data = self.features.get_mut(feature_name).ok_or(eyre!(
             "couldnt find feature {} trying to set as workspace feature for {}",
@@ -162,12 +471,19 @@ impl Dependency {
     }
 
     pub fn disable_feature(&mut self, feature_name: &str) -> Result<()> {
+        self.disable_feature_and_dependents(feature_name)?;
+
+        // features shared with a feature that could not be disabled (e.g. one enabled by the workspace) are still required
+        self.enable_required_sub_features()
+    }
+
+    fn disable_feature_and_dependents(&mut self, feature_name: &str) -> Result<()> {
         let data = self
             .features
             .get_mut(feature_name)
             .context(format!("could not find {}", feature_name))?;
 
-        if !data.is_enabled() {
+        if !data.is_enabled() || !data.is_toggleable() {
             //early return to prevent loop
             return Ok(());
         }
@@ -175,7 +491,26 @@ impl Dependency {
         data.enabled_state = EnabledState::Normal(false);
 
         for name in self.get_dependent_features(feature_name) {
-            self.disable_feature(&name)?
+            self.disable_feature_and_dependents(&name)?
+        }
+
+        Ok(())
+    }
+
+    /// enables all sub features which are required by a currently enabled feature
+    fn enable_required_sub_features(&mut self) -> Result<()> {
+        let required = self
+            .features
+            .values()
+            .filter(|data| data.is_enabled())
+            .flat_map(|data| &data.sub_features)
+            .filter(|sub_feature| sub_feature.kind == SubFeatureType::Normal)
+            .filter(|sub_feature| self.features.contains_key(&sub_feature.name))
+            .map(|sub_feature| sub_feature.name.to_string())
+            .collect_vec();
+
+        for name in required {
+            self.enable_feature(&name)?;
         }
 
         Ok(())
@@ -209,12 +544,14 @@ impl Dependency {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DependencyType {
     Normal,
     Development,
     Build,
     Workspace,
+    /// the `[features]` of the package itself
+    Features,
     Unknown,
 }
 
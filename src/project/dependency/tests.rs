@@ -0,0 +1,778 @@
+#![allow(clippy::unwrap_used)]
+
+use crate::project::dependency::builder::DependencyBuilder;
+use crate::project::dependency::Dependency;
+use itertools::Itertools;
+
+fn enabled_features(dependency: &Dependency) -> Vec<&str> {
+    dependency
+        .features
+        .iter()
+        .filter(|(_, data)| data.is_enabled())
+        .map(|(name, _)| name.as_str())
+        .sorted()
+        .collect()
+}
+
+/// `full` -> `net` -> `io` -> `bytes`, `rt` is independent
+fn chain() -> DependencyBuilder {
+    DependencyBuilder::new("chain")
+        .feature("full", &["net", "rt"])
+        .feature("net", &["io"])
+        .feature("io", &["bytes"])
+        .feature("bytes", &[])
+        .feature("rt", &[])
+        .feature("extra", &[])
+}
+
+#[test]
+fn nothing_is_enabled_by_default() {
+    let dependency = chain().build().unwrap();
+
+    assert!(enabled_features(&dependency).is_empty());
+}
+
+#[test]
+fn enabling_a_feature_enables_its_sub_features_transitively() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.enable_feature("net").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["bytes", "io", "net"]);
+}
+
+#[test]
+fn enabling_a_feature_enables_all_branches() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.enable_feature("full").unwrap();
+
+    assert_eq!(
+        enabled_features(&dependency),
+        ["bytes", "full", "io", "net", "rt"]
+    );
+}
+
+#[test]
+fn enabling_a_leaf_does_not_enable_its_parents() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.enable_feature("bytes").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["bytes"]);
+}
+
+#[test]
+fn enabling_an_unknown_feature_fails() {
+    let mut dependency = chain().build().unwrap();
+
+    assert!(dependency.enable_feature("unknown").is_err());
+    assert!(enabled_features(&dependency).is_empty());
+}
+
+#[test]
+fn enabling_ignores_dependency_sub_features() {
+    let mut dependency = DependencyBuilder::new("deps")
+        .feature("serde", &["dep:serde", "other/serde", "std"])
+        .feature("std", &[])
+        .build()
+        .unwrap();
+
+    dependency.enable_feature("serde").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["serde", "std"]);
+}
+
+#[test]
+fn disabling_a_feature_disables_the_features_depending_on_it() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.enable_feature("full").unwrap();
+    dependency.disable_feature("io").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["bytes", "rt"]);
+}
+
+#[test]
+fn disabling_a_feature_keeps_its_sub_features() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.enable_feature("full").unwrap();
+    dependency.disable_feature("full").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["bytes", "io", "net", "rt"]);
+}
+
+#[test]
+fn disabling_a_feature_does_not_affect_unrelated_features() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.enable_feature("net").unwrap();
+    dependency.enable_feature("extra").unwrap();
+    dependency.disable_feature("bytes").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["extra"]);
+}
+
+#[test]
+fn disabling_a_disabled_feature_does_nothing() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.enable_feature("rt").unwrap();
+    dependency.disable_feature("net").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["rt"]);
+}
+
+/// `a` and `b` both require `shared`, which requires `base`
+fn diamond() -> DependencyBuilder {
+    DependencyBuilder::new("diamond")
+        .feature("a", &["shared"])
+        .feature("b", &["shared"])
+        .feature("shared", &["base"])
+        .feature("base", &[])
+}
+
+#[test]
+fn disabling_one_side_of_a_diamond_keeps_the_shared_feature() {
+    let mut dependency = diamond().enabled(&["a", "b"]).build().unwrap();
+
+    dependency.disable_feature("a").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["b", "base", "shared"]);
+
+    dependency.disable_feature("b").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["base", "shared"]);
+}
+
+#[test]
+fn disabling_the_shared_feature_of_a_diamond_disables_both_sides() {
+    let mut dependency = diamond().enabled(&["a", "b"]).build().unwrap();
+
+    dependency.disable_feature("shared").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["base"]);
+}
+
+#[test]
+fn disabling_a_shared_feature_required_by_the_workspace_keeps_it() {
+    let mut dependency = diamond().enabled(&["a"]).build().unwrap();
+
+    dependency.set_feature_to_workspace("b").unwrap();
+    dependency.disable_feature("base").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["b", "base", "shared"]);
+    assert!(!dependency.get_feature("a").unwrap().is_enabled());
+}
+
+#[test]
+fn disabling_never_disables_workspace_features() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.set_feature_to_workspace("full").unwrap();
+    dependency.disable_feature("rt").unwrap();
+
+    assert_eq!(
+        enabled_features(&dependency),
+        ["bytes", "full", "io", "net", "rt"]
+    );
+}
+
+#[test]
+fn cyclic_features_do_not_loop_when_enabling() {
+    let mut dependency = DependencyBuilder::new("cycle")
+        .feature("a", &["b"])
+        .feature("b", &["c"])
+        .feature("c", &["a"])
+        .feature("d", &[])
+        .build()
+        .unwrap();
+
+    dependency.enable_feature("b").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["a", "b", "c"]);
+}
+
+#[test]
+fn cyclic_features_do_not_loop_when_disabling() {
+    let mut dependency = DependencyBuilder::new("cycle")
+        .feature("a", &["b"])
+        .feature("b", &["c"])
+        .feature("c", &["a"])
+        .enabled(&["a"])
+        .build()
+        .unwrap();
+
+    dependency.disable_feature("a").unwrap();
+
+    assert!(enabled_features(&dependency).is_empty());
+}
+
+#[test]
+fn self_referencing_features_do_not_loop() {
+    let mut dependency = DependencyBuilder::new("self")
+        .feature("a", &["a"])
+        .build()
+        .unwrap();
+
+    dependency.enable_feature("a").unwrap();
+    assert_eq!(enabled_features(&dependency), ["a"]);
+
+    dependency.disable_feature("a").unwrap();
+    assert!(enabled_features(&dependency).is_empty());
+}
+
+#[test]
+fn toggling_switches_the_feature() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.toggle_feature("net").unwrap();
+    assert_eq!(enabled_features(&dependency), ["bytes", "io", "net"]);
+
+    dependency.toggle_feature("net").unwrap();
+    assert_eq!(enabled_features(&dependency), ["bytes", "io"]);
+}
+
+#[test]
+fn toggling_a_workspace_feature_does_nothing() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.set_feature_to_workspace("rt").unwrap();
+    dependency.toggle_feature("rt").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["rt"]);
+    assert!(!dependency.get_feature("rt").unwrap().is_toggleable());
+}
+
+#[test]
+fn toggling_an_unknown_feature_fails() {
+    let mut dependency = chain().build().unwrap();
+
+    assert!(dependency.toggle_feature("unknown").is_err());
+}
+
+#[test]
+fn enabled_features_are_applied_when_building() {
+    let dependency = chain().enabled(&["net", "extra"]).build().unwrap();
+
+    assert_eq!(
+        enabled_features(&dependency),
+        ["bytes", "extra", "io", "net"]
+    );
+}
+
+#[test]
+fn default_features_are_enabled_when_used() {
+    let dependency = chain().feature("default", &["rt"]).build().unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["rt"]);
+    assert!(dependency.get_feature("rt").unwrap().is_default);
+    assert!(!dependency.get_feature("net").unwrap().is_default);
+}
+
+#[test]
+fn default_features_are_not_enabled_without_default_features() {
+    let dependency = chain()
+        .feature("default", &["rt"])
+        .default_features(false)
+        .build()
+        .unwrap();
+
+    assert!(enabled_features(&dependency).is_empty());
+    assert!(!dependency.can_use_default());
+}
+
+#[test]
+fn disabling_a_default_feature_prevents_using_defaults() {
+    let mut dependency = chain().feature("default", &["rt"]).build().unwrap();
+
+    assert!(dependency.can_use_default());
+
+    dependency.disable_feature("rt").unwrap();
+
+    assert!(!dependency.can_use_default());
+    assert_eq!(enabled_features(&dependency), Vec::<&str>::new());
+}
+
+#[test]
+fn features_to_enable_only_contains_top_level_features() {
+    let dependency = chain().enabled(&["net", "rt"]).build().unwrap();
+
+    assert_eq!(dependency.get_features_to_enable(), ["net", "rt"]);
+}
+
+#[test]
+fn features_to_enable_skips_defaults_when_they_are_used() {
+    let dependency = chain()
+        .feature("default", &["rt"])
+        .enabled(&["extra"])
+        .build()
+        .unwrap();
+
+    assert_eq!(dependency.get_features_to_enable(), ["extra"]);
+}
+
+#[test]
+fn features_to_enable_contains_defaults_when_they_are_not_used() {
+    let mut dependency = chain()
+        .feature("default", &["rt", "extra"])
+        .build()
+        .unwrap();
+
+    dependency.disable_feature("extra").unwrap();
+
+    assert_eq!(dependency.get_features_to_enable(), ["rt"]);
+}
+
+#[test]
+fn features_to_enable_skips_workspace_features() {
+    let mut dependency = chain().enabled(&["extra"]).build().unwrap();
+
+    dependency.set_feature_to_workspace("rt").unwrap();
+
+    assert_eq!(dependency.get_features_to_enable(), ["extra"]);
+}
+
+#[test]
+fn currently_dependent_features_only_contains_enabled_parents() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.enable_feature("net").unwrap();
+
+    assert_eq!(dependency.get_currently_dependent_features("io"), ["net"]);
+    assert!(dependency
+        .get_currently_dependent_features("net")
+        .is_empty());
+    assert!(dependency.get_currently_dependent_features("rt").is_empty());
+}
+
+#[test]
+fn optional_dependencies_are_detected() {
+    let mut dependency = DependencyBuilder::new("deps")
+        .feature("serde", &["dep:serde"])
+        .feature("json", &["dep:serde_json", "serde"])
+        .feature("std", &[])
+        .build()
+        .unwrap();
+
+    assert!(dependency.is_optional_dependency("serde"));
+    assert!(!dependency.is_optional_dependency("json"));
+    assert!(!dependency.is_optional_dependency("std"));
+    assert!(!dependency.is_optional_dependency("unknown"));
+
+    dependency.enable_feature("json").unwrap();
+
+    assert_eq!(
+        dependency.get_enabled_optional_dependencies(),
+        ["serde", "serde_json"]
+    );
+}
+
+#[test]
+fn feature_counts_exclude_default() {
+    let dependency = chain().feature("default", &["rt"]).build().unwrap();
+
+    assert_eq!(dependency.get_features_count(), 6);
+    assert_eq!(dependency.get_enabled_features_count(), 1);
+}
+
+#[test]
+fn setting_enabled_features_replaces_the_current_ones() {
+    let mut dependency = chain().enabled(&["net", "extra"]).build().unwrap();
+
+    dependency
+        .set_enabled_features(&["rt".to_string(), "unknown".to_string()])
+        .unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["rt"]);
+    assert_eq!(dependency.get_own_enabled_features(), ["rt"]);
+}
+
+/// `default` enables `std` & `alloc` - `alloc` requires `core`
+fn with_defaults() -> DependencyBuilder {
+    DependencyBuilder::new("defaults")
+        .feature("default", &["std", "alloc"])
+        .feature("std", &[])
+        .feature("alloc", &["core"])
+        .feature("core", &[])
+        .feature("serde", &[])
+}
+
+#[test]
+fn defaults_without_explicit_features() {
+    let dependency = with_defaults().build().unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["alloc", "core", "std"]);
+    assert!(dependency.can_use_default());
+}
+
+#[test]
+fn defaults_with_explicit_features_overlapping_the_defaults() {
+    let dependency = with_defaults().enabled(&["std", "serde"]).build().unwrap();
+
+    assert_eq!(
+        enabled_features(&dependency),
+        ["alloc", "core", "serde", "std"]
+    );
+    assert_eq!(dependency.get_features_to_enable(), ["serde"]);
+}
+
+#[test]
+fn no_defaults_without_explicit_features() {
+    let dependency = with_defaults().default_features(false).build().unwrap();
+
+    assert!(enabled_features(&dependency).is_empty());
+    assert!(!dependency.can_use_default());
+}
+
+#[test]
+fn no_defaults_with_explicit_features_overlapping_the_defaults() {
+    let dependency = with_defaults()
+        .default_features(false)
+        .enabled(&["std", "serde"])
+        .build()
+        .unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["serde", "std"]);
+    assert!(!dependency.can_use_default());
+    assert_eq!(dependency.get_features_to_enable(), ["serde", "std"]);
+}
+
+#[test]
+fn no_defaults_with_the_default_feature_enables_all_defaults() {
+    let dependency = with_defaults()
+        .default_features(false)
+        .enabled(&["default"])
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        enabled_features(&dependency),
+        ["alloc", "core", "default", "std"]
+    );
+}
+
+#[test]
+fn unknown_enabled_features_are_kept_as_custom_features() {
+    let dependency = chain().enabled(&["net", "unreleased"]).build().unwrap();
+
+    assert_eq!(
+        enabled_features(&dependency),
+        ["bytes", "io", "net", "unreleased"]
+    );
+    assert!(dependency.get_feature("unreleased").unwrap().is_custom);
+    assert_eq!(dependency.get_features_to_enable(), ["net", "unreleased"]);
+}
+
+#[test]
+fn adding_a_custom_feature_only_adds_unknown_features() {
+    let mut dependency = chain().build().unwrap();
+
+    assert!(!dependency.add_custom_feature("net").unwrap());
+    assert!(!dependency.get_feature("net").unwrap().is_custom);
+
+    assert!(dependency.add_custom_feature("unreleased").unwrap());
+    assert_eq!(
+        enabled_features(&dependency),
+        ["bytes", "io", "net", "unreleased"]
+    );
+}
+
+/// writes the dependency like it would be saved and loads it again - the enabled features have to stay the same
+fn assert_round_trip(
+    builder: impl Fn() -> DependencyBuilder,
+    enabled: &[&str],
+    uses_default: bool,
+) {
+    let original = builder()
+        .enabled(enabled)
+        .default_features(uses_default)
+        .build()
+        .unwrap();
+
+    let written = original.get_features_to_enable();
+
+    let loaded = builder()
+        .enabled(&written.iter().map(String::as_str).collect::<Vec<_>>())
+        .default_features(original.can_use_default())
+        .build()
+        .unwrap();
+
+    // `default` itself is only marked as enabled if it was enabled explicitly
+    let without_default = |dependency: &Dependency| {
+        enabled_features(dependency)
+            .into_iter()
+            .filter(|name| *name != "default")
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(
+        without_default(&loaded),
+        without_default(&original),
+        "enabled {:?} with default-features = {} was written as {:?}",
+        enabled,
+        uses_default,
+        written
+    );
+}
+
+#[test]
+fn round_trip_keeps_the_enabled_features() {
+    for uses_default in [true, false] {
+        assert_round_trip(chain, &[], uses_default);
+        assert_round_trip(chain, &["full"], uses_default);
+        assert_round_trip(chain, &["io", "extra"], uses_default);
+
+        assert_round_trip(diamond, &["a"], uses_default);
+        assert_round_trip(diamond, &["a", "b", "base"], uses_default);
+
+        assert_round_trip(with_defaults, &[], uses_default);
+        assert_round_trip(with_defaults, &["std"], uses_default);
+        assert_round_trip(with_defaults, &["alloc", "serde"], uses_default);
+        assert_round_trip(with_defaults, &["default"], uses_default);
+    }
+}
+
+#[test]
+fn round_trip_keeps_default_features_enabled_by_other_features() {
+    let builder = || with_defaults().feature("full", &["std", "serde"]);
+
+    assert_round_trip(builder, &["full"], true);
+    assert_round_trip(builder, &["full"], false);
+    assert_round_trip(builder, &["full", "alloc"], false);
+}
+
+#[test]
+fn round_trip_keeps_cyclic_features() {
+    let builder = || {
+        DependencyBuilder::new("cycle")
+            .feature("a", &["b"])
+            .feature("b", &["a"])
+            .feature("c", &[])
+    };
+
+    assert_round_trip(builder, &["a"], true);
+    assert_round_trip(builder, &["a", "c"], true);
+
+    let dependency = builder().enabled(&["b"]).build().unwrap();
+
+    assert_eq!(dependency.get_features_to_enable(), ["a"]);
+}
+
+#[test]
+fn toggling_explicitly_only_enables_the_feature_itself() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.toggle_feature_explicit("net").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["net"]);
+    assert!(dependency.is_implied("io"));
+    assert!(dependency.is_implied("bytes"));
+    assert!(!dependency.is_implied("net"));
+    assert!(!dependency.is_implied("rt"));
+    assert_eq!(dependency.get_features_to_enable(), ["net"]);
+}
+
+#[test]
+fn toggling_explicitly_keeps_the_dependent_features() {
+    let mut dependency = chain().enabled(&["full"]).build().unwrap();
+
+    dependency.toggle_feature_explicit("net").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["bytes", "full", "io", "rt"]);
+    assert!(dependency.is_implied("net"));
+    assert_eq!(dependency.get_features_to_enable(), ["full"]);
+
+    dependency.toggle_feature_explicit("net").unwrap();
+
+    assert!(!dependency.is_implied("net"));
+    assert_eq!(dependency.get_features_to_enable(), ["full"]);
+}
+
+#[test]
+fn implied_features_activate_optional_dependencies() {
+    let mut dependency = DependencyBuilder::new("deps")
+        .feature("full", &["json"])
+        .feature("json", &["dep:serde_json"])
+        .build()
+        .unwrap();
+
+    dependency.toggle_feature_explicit("full").unwrap();
+
+    assert_eq!(
+        dependency.get_enabled_optional_dependencies(),
+        ["serde_json"]
+    );
+}
+
+#[test]
+fn dependency_features_activate_optional_dependencies() {
+    let mut dependency = DependencyBuilder::new("deps")
+        .feature("json", &["dep:serde_json"])
+        .feature("pretty", &["serde_json/std", "other/std"])
+        .feature("lazy", &["serde_json?/std"])
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        dependency
+            .get_feature("pretty")
+            .unwrap()
+            .sub_features
+            .iter()
+            .map(|sub_feature| sub_feature.name.as_str())
+            .collect_vec(),
+        ["dep:serde_json"]
+    );
+
+    dependency.enable_feature("lazy").unwrap();
+    assert!(dependency.get_enabled_optional_dependencies().is_empty());
+
+    dependency.enable_feature("pretty").unwrap();
+    assert_eq!(
+        dependency.get_enabled_optional_dependencies(),
+        ["serde_json"]
+    );
+    assert_eq!(dependency.get_features_to_enable(), ["lazy", "pretty"]);
+}
+
+#[test]
+fn enabled_conflicting_features_are_found() {
+    let mut dependency = DependencyBuilder::new("sqlx")
+        .feature("runtime-tokio", &[])
+        .feature("runtime-async-std", &[])
+        .feature("runtime-smol", &[])
+        .feature("full", &["runtime-tokio", "runtime-smol"])
+        .conflict(&["runtime-tokio", "runtime-async-std", "runtime-smol"])
+        .enabled(&["runtime-async-std"])
+        .build()
+        .unwrap();
+
+    assert!(dependency.get_enabled_conflicts().is_empty());
+
+    dependency.enable_feature("full").unwrap();
+
+    assert_eq!(
+        dependency.get_enabled_conflicts(),
+        [
+            ("runtime-async-std".to_string(), "runtime-smol".to_string()),
+            ("runtime-async-std".to_string(), "runtime-tokio".to_string()),
+            ("runtime-smol".to_string(), "runtime-tokio".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn features_sharing_the_name_of_an_optional_dependency_stay_distinct() {
+    // `serde` activates the optional dependency of the same name but also more
+    let mut dependency = DependencyBuilder::new("deps")
+        .feature("serde", &["dep:serde", "std", "serde_json?/std"])
+        .feature("json", &["dep:serde_json"])
+        .feature("serde_json", &["dep:serde_json"])
+        .feature("std", &[])
+        .build()
+        .unwrap();
+
+    assert_eq!(dependency.get_features_count(), 4);
+    assert!(!dependency.is_optional_dependency("serde"));
+    assert!(dependency.is_optional_dependency("serde_json"));
+    assert!(!dependency.is_optional_dependency("json"));
+
+    dependency.enable_feature("serde").unwrap();
+
+    assert_eq!(enabled_features(&dependency), ["serde", "std"]);
+    assert_eq!(dependency.get_enabled_optional_dependencies(), ["serde"]);
+    assert_eq!(dependency.get_features_to_enable(), ["serde"]);
+
+    dependency.enable_feature("json").unwrap();
+
+    assert_eq!(
+        dependency.get_enabled_optional_dependencies(),
+        ["serde", "serde_json"]
+    );
+    assert!(!dependency.get_feature("serde_json").unwrap().is_enabled());
+}
+
+#[test]
+fn enabling_all_features_allows_the_defaults() {
+    let mut dependency = with_defaults().default_features(false).build().unwrap();
+
+    dependency.enable_all_features().unwrap();
+
+    assert!(dependency.can_use_default());
+    assert_eq!(dependency.get_features_to_enable(), ["serde"]);
+}
+
+#[test]
+fn disabling_the_default_features_keeps_the_explicit_features() {
+    let mut dependency = with_defaults().enabled(&["serde"]).build().unwrap();
+
+    dependency.disable_default_features().unwrap();
+
+    assert!(!dependency.can_use_default());
+    assert_eq!(enabled_features(&dependency), ["serde"]);
+    assert_eq!(dependency.get_features_to_enable(), ["serde"]);
+}
+
+#[test]
+fn the_delta_lists_the_changes_to_the_defaults() {
+    let mut dependency = with_defaults().build().unwrap();
+
+    assert_eq!(dependency.get_default_delta(), (vec![], vec![]));
+
+    dependency.disable_feature("std").unwrap();
+    dependency.enable_feature("serde").unwrap();
+
+    assert_eq!(
+        dependency.get_default_delta(),
+        (vec!["serde".to_string()], vec!["std".to_string()])
+    );
+}
+
+#[test]
+fn the_delta_skips_features_enabled_by_added_features() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.enable_feature("net").unwrap();
+
+    assert_eq!(
+        dependency.get_default_delta(),
+        (vec!["net".to_string()], vec![])
+    );
+}
+
+#[test]
+fn the_sets_of_all_features_match_the_single_lookups() {
+    let mut dependency = chain().build().unwrap();
+
+    dependency.enable_feature("net").unwrap();
+    dependency.toggle_feature_explicit("io").unwrap();
+
+    for name in ["full", "net", "io", "bytes", "rt", "extra"] {
+        assert_eq!(
+            dependency.get_implied_features().contains(name),
+            dependency.is_implied(name),
+            "{}",
+            name
+        );
+        assert_eq!(
+            dependency.get_features_required_by_enabled().contains(name),
+            !dependency.get_currently_dependent_features(name).is_empty(),
+            "{}",
+            name
+        );
+    }
+}
+
+#[test]
+fn the_docs_are_of_the_resolved_version() {
+    let mut dependency = chain().version(">=1, <3").build().unwrap();
+
+    assert_eq!(dependency.get_docs_url(), "https://docs.rs/chain/latest");
+
+    dependency.resolved_version = Some("2.4.1".to_string());
+
+    assert_eq!(dependency.get_docs_url(), "https://docs.rs/chain/2.4.1");
+}
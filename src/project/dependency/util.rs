@@ -7,6 +7,7 @@ pub fn get_path(kind: &DependencyType, target: &Option<Platform>) -> String {
         DependencyType::Development => "dev-dependencies",
         DependencyType::Build => "build-dependencies",
         DependencyType::Workspace => "workspace.dependencies",
+        DependencyType::Features => "features",
         DependencyType::Unknown => "dependencies",
     };
 
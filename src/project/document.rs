@@ -1,29 +1,63 @@
 use color_eyre::eyre::{bail, eyre, ContextCompat};
 
+use color_eyre::eyre::WrapErr;
 use color_eyre::Result;
 use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+use toml_edit::DocumentMut;
 
-use crate::parsing::package::get_packages;
+use crate::parsing::keep::KeepEntry;
+use crate::parsing::package::{get_packages, Project};
 use crate::project::dependency::feature::EnabledState;
 use crate::project::package::Package;
+use crate::util::{get_config_bool, toml_document_from_path};
 
 pub struct Document {
     packages: Vec<Package>,
     workspace_index: Option<usize>,
+    /// the directory of the root manifest
+    root_path: String,
+    /// the entries of `workspace.cargo-features-manager.keep` - including the ones of the `keep-file`
+    workspace_keep: HashMap<String, KeepEntry>,
+    /// always write `default-features` - even if it is `true`
+    explicit_default_features: bool,
+    /// cargo may neither access the network nor update the Cargo.lock
+    frozen: bool,
+    /// the dependencies that could not be parsed while loading - they are left out
+    load_errors: Vec<String>,
+    /// the manifests written in scratch mode by path - the files stay unchanged until the changes are applied
+    scratch: Option<BTreeMap<String, String>>,
 }
 
 impl Document {
-    pub fn new() -> Result<Document> {
-        let (mut packages, workspace) = get_packages()?;
-
-        if packages.len() == 1
-            && packages
-                .first()
-                .context("no package found")?
-                .dependencies
-                .is_empty()
-        {
-            bail!("no dependencies were found")
+    /// loads the project of the current directory - like cargo itself
+    pub fn new(frozen: bool) -> Result<Document> {
+        Self::load(None, frozen, false)
+    }
+
+    /// like `new` - prints the loading progress to stderr
+    pub fn new_with_progress(frozen: bool) -> Result<Document> {
+        Self::load(None, frozen, true)
+    }
+
+    /// loads the project the manifest belongs to - e.g. `path/to/project/Cargo.toml`
+    pub fn from_manifest_path<P: AsRef<Path>>(manifest_path: P, frozen: bool) -> Result<Document> {
+        Self::load(Some(manifest_path.as_ref()), frozen, false)
+    }
+
+    fn load(manifest_path: Option<&Path>, frozen: bool, progress: bool) -> Result<Document> {
+        let Project {
+            mut packages,
+            workspace,
+            root_path,
+            workspace_keep,
+            errors,
+        } = get_packages(manifest_path, frozen, progress)?;
+
+        if packages.is_empty() {
+            bail!("no package was found")
         }
 
         let mut workspace_index = None;
@@ -37,6 +71,13 @@ impl Document {
         let mut document = Document {
             packages,
             workspace_index,
+            explicit_default_features: get_config_bool(&root_path, "explicit-default-features")?
+                .unwrap_or_default(),
+            root_path,
+            workspace_keep,
+            frozen,
+            load_errors: errors,
+            scratch: None,
         };
 
         document.update_workspace_deps()?;
@@ -98,6 +139,46 @@ impl Document {
         Ok(())
     }
 
+    /// applies the enabled features of the dependency to the same dependency of all other packages
+    ///
+    /// dependencies inherited from the workspace are skipped - returns the names of the changed packages
+    pub fn apply_features_to_all_packages(
+        &mut self,
+        package_name: &str,
+        dep_name: &str,
+    ) -> Result<Vec<String>> {
+        let features = self
+            .get_package(package_name)?
+            .get_dep(dep_name)?
+            .get_own_enabled_features();
+
+        let mut changed = vec![];
+
+        for (index, package) in self.packages.iter_mut().enumerate() {
+            if package.name == package_name || Some(index) == self.workspace_index {
+                continue;
+            }
+
+            let Ok(dep) = package.get_dep_mut(dep_name) else {
+                continue;
+            };
+
+            if dep.workspace || dep.is_own_features() {
+                continue;
+            }
+
+            let previous = dep.get_own_enabled_features();
+
+            dep.set_enabled_features(&features)?;
+
+            if dep.get_own_enabled_features() != previous {
+                changed.push(package.name.to_string());
+            }
+        }
+
+        Ok(changed)
+    }
+
     pub fn get_packages(&self) -> &Vec<Package> {
         &self.packages
     }
@@ -125,7 +206,68 @@ impl Document {
     pub fn workspace_index(&self) -> Option<usize> {
         self.workspace_index
     }
+    pub fn root_path(&self) -> &str {
+        &self.root_path
+    }
+
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// the dependencies that could not be parsed - e.g. `could not parse dependency a of b - ...`
+    pub fn get_load_errors(&self) -> &[String] {
+        &self.load_errors
+    }
+
+    pub fn workspace_keep(&self) -> &HashMap<String, KeepEntry> {
+        &self.workspace_keep
+    }
+
+    pub fn explicit_default_features(&self) -> bool {
+        self.explicit_default_features
+    }
+
     pub fn is_workspace(&self) -> bool {
         self.packages.len() > 1
     }
+
+    /// keeps all following writes of manifests in memory - see `take_scratch`
+    pub(crate) fn start_scratch(&mut self) {
+        self.scratch = Some(BTreeMap::new());
+    }
+
+    pub(crate) fn is_scratch(&self) -> bool {
+        self.scratch.is_some()
+    }
+
+    /// returns the manifests written in scratch mode by path - the content they would have
+    pub(crate) fn take_scratch(&mut self) -> BTreeMap<String, String> {
+        self.scratch.take().unwrap_or_default()
+    }
+
+    /// reads the manifest - in scratch mode the written content if it was already written
+    pub(crate) fn read_manifest(&self, manifest_path: &str) -> Result<DocumentMut> {
+        match self
+            .scratch
+            .as_ref()
+            .and_then(|scratch| scratch.get(manifest_path))
+        {
+            Some(content) => content
+                .parse()
+                .wrap_err_with(|| format!("could not parse {:?}", manifest_path)),
+            None => toml_document_from_path(manifest_path),
+        }
+    }
+
+    /// writes the manifest - in scratch mode it is only kept in memory
+    pub(crate) fn write_manifest(&mut self, manifest_path: &str, content: String) -> Result<()> {
+        match &mut self.scratch {
+            Some(scratch) => {
+                scratch.insert(manifest_path.to_string(), content);
+                Ok(())
+            }
+            None => fs::write(manifest_path, content)
+                .wrap_err_with(|| format!("could not write {}", manifest_path)),
+        }
+    }
 }
@@ -1,11 +1,20 @@
-use crate::project::dependency::Dependency;
-use color_eyre::eyre::{bail, eyre};
+use crate::parsing::keep::KeepEntry;
+use crate::project::dependency::{Dependency, DependencyType};
+use color_eyre::eyre::{bail, eyre, Report};
+use itertools::Itertools;
+use std::collections::HashMap;
+use strsim::levenshtein;
 
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+
+#[non_exhaustive]
 pub struct Package {
     pub dependencies: Vec<Dependency>,
     pub name: String,
     // path include the Cargo.toml
     pub manifest_path: String,
+    /// the entries of `cargo-features-manager.keep` in the manifest of the package
+    pub keep: HashMap<String, KeepEntry>,
 }
 
 impl Package {
@@ -22,14 +31,46 @@ impl Package {
         }
     }
 
+    /// the dependency by its crate name & kind - e.g. `insta` as a dev dependency
+    pub fn get_dep_by_kind(
+        &self,
+        name: &str,
+        kind: DependencyType,
+    ) -> color_eyre::Result<&Dependency> {
+        self.dependencies
+            .iter()
+            .find(|dep| dep.name == name && dep.kind == kind)
+            .ok_or_else(|| self.dependency_not_found(name))
+    }
+
     pub fn get_dep_index(&self, name: &String) -> color_eyre::Result<usize> {
-        Ok(self
+        self.dependencies
+            .iter()
+            .position(|dep| dep.get_name() == *name || dep.name == *name)
+            .ok_or_else(|| self.dependency_not_found(name))
+    }
+
+    fn dependency_not_found(&self, name: &str) -> Report {
+        let suggestions = self
             .dependencies
             .iter()
-            .enumerate()
-            .find(|(_, dep)| dep.get_name() == *name)
-            .ok_or(eyre!("dependency \"{}\" could not be found", name))?
-            .0)
+            .map(|dep| (levenshtein(&dep.name, name), &dep.name))
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .sorted()
+            .map(|(_, name)| name)
+            .dedup()
+            .take(3)
+            .join(", ");
+
+        if suggestions.is_empty() {
+            eyre!("dependency \"{}\" could not be found", name)
+        } else {
+            eyre!(
+                "dependency \"{}\" could not be found - did you mean: {}",
+                name,
+                suggestions
+            )
+        }
     }
 
     pub fn get_dep_mut(&mut self, name: &str) -> color_eyre::Result<&mut Dependency> {
@@ -43,4 +84,109 @@ impl Package {
             Some(some) => Ok(some),
         }
     }
+
+    pub fn get_dep_by_kind_mut(
+        &mut self,
+        name: &str,
+        kind: DependencyType,
+    ) -> color_eyre::Result<&mut Dependency> {
+        let error = self.dependency_not_found(name);
+
+        self.dependencies
+            .iter_mut()
+            .find(|dep| dep.name == name && dep.kind == kind)
+            .ok_or(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use crate::project::dependency::builder::DependencyBuilder;
+    use crate::project::dependency::DependencyType;
+    use crate::project::package::Package;
+
+    fn package() -> Package {
+        let serde = || {
+            DependencyBuilder::new("serde")
+                .feature("derive", &[])
+                .feature("std", &[])
+        };
+
+        Package {
+            dependencies: vec![
+                serde().build().unwrap(),
+                serde()
+                    .kind(DependencyType::Development)
+                    .enabled(&["derive"])
+                    .build()
+                    .unwrap(),
+            ],
+            name: "package".to_string(),
+            manifest_path: "Cargo.toml".to_string(),
+            keep: Default::default(),
+        }
+    }
+
+    #[test]
+    fn dependencies_of_different_kinds_stay_independent() {
+        let mut package = package();
+
+        let normal_name = package.dependencies[0].get_name();
+        let dev_name = package.dependencies[1].get_name();
+
+        assert_ne!(normal_name, dev_name);
+
+        package
+            .get_dep_mut(&normal_name)
+            .unwrap()
+            .enable_feature("std")
+            .unwrap();
+        package
+            .get_dep_mut(&dev_name)
+            .unwrap()
+            .disable_feature("derive")
+            .unwrap();
+
+        assert_eq!(
+            package
+                .get_dep(&normal_name)
+                .unwrap()
+                .get_features_to_enable(),
+            ["std"]
+        );
+        assert!(package
+            .get_dep(&dev_name)
+            .unwrap()
+            .get_features_to_enable()
+            .is_empty());
+    }
+
+    #[test]
+    fn dependencies_are_found_by_their_kind() {
+        let mut package = package();
+
+        package
+            .get_dep_by_kind_mut("serde", DependencyType::Development)
+            .unwrap()
+            .enable_feature("std")
+            .unwrap();
+
+        assert!(package
+            .get_dep_by_kind("serde", DependencyType::Normal)
+            .unwrap()
+            .get_features_to_enable()
+            .is_empty());
+        assert_eq!(
+            package
+                .get_dep_by_kind("serde", DependencyType::Development)
+                .unwrap()
+                .get_features_to_enable(),
+            ["derive", "std"]
+        );
+        assert!(package
+            .get_dep_by_kind("serde", DependencyType::Build)
+            .is_err());
+    }
 }
@@ -1,40 +1,1099 @@
+use clap::{Args, ValueEnum};
 use color_eyre::Result;
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
 
-use console::{style, Term};
+use console::{measure_text_width, pad_str, style, Alignment, Term};
 use std::io::Write;
 use std::ops::Not;
-use std::path::Path;
 
-use crate::project::dependency::Dependency;
+use crate::parsing::keep::PruneTrust;
+use crate::project::dependency::util::get_path;
+use crate::project::dependency::{Dependency, DependencyType};
 use crate::project::document::Document;
+use crate::project::package::Package;
 use crate::save::save_dependency;
-use crate::util::{get_item_from_doc, toml_document_from_path};
-use color_eyre::eyre::{eyre, ContextCompat};
+use crate::util::{get_config_string, implicit_table, toml_document_from_path};
+use color_eyre::eyre::{bail, eyre, ContextCompat, WrapErr};
 use itertools::Itertools;
-use std::process::{Command, Stdio};
+use std::path::{Path, PathBuf};
+use std::process::{exit, Child, Command, Stdio};
+use std::time::{Duration, Instant};
+use std::{env, fs, thread};
+use toml_edit::{Array, DocumentMut, Item, TableLike, Value};
+use tracing::{debug, warn};
+
+#[derive(Args)]
+pub struct PruneArgs {
+    #[arg(long, short)]
+    dry_run: bool,
+    /// add features which are required to compile to `cargo-features-manager.keep`
+    #[arg(long)]
+    update_keep: bool,
+    /// exit with a non-zero code if any features could be removed
+    #[arg(long)]
+    check: bool,
+    /// start & update the keep sections without asking for confirmation
+    #[arg(long, short)]
+    yes: bool,
+    /// how the removable features are listed - `github` prints annotations for GitHub Actions
+    #[arg(long, value_enum, default_value = "human")]
+    format: OutputFormat,
+    /// only list the features no other feature requires - instant but unverified, nothing is compiled or changed
+    #[arg(long, conflicts_with_all = ["update_keep", "check"])]
+    no_build: bool,
+    #[command(flatten)]
+    options: PruneOptions,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OutputFormat {
+    /// `- crate: feature` lines - e.g. for a commit message
+    Human,
+    /// one `::warning` per feature - shown as an annotation on the dependency in its manifest
+    Github,
+}
+
+#[derive(Args)]
+pub struct MinimizeArgs {
+    /// start & apply the removals without asking for confirmation
+    #[arg(long, short)]
+    yes: bool,
+    #[command(flatten)]
+    options: PruneOptions,
+}
+
+#[derive(Args)]
+pub struct DefaultsArgs {
+    /// start & apply the changes without asking for confirmation
+    #[arg(long, short)]
+    yes: bool,
+    #[command(flatten)]
+    options: PruneOptions,
+}
 
-pub fn prune(mut document: Document, is_dry_run: bool, skip_tests: bool) -> Result<()> {
+/// options shared by all commands which check features by compiling
+#[derive(Args)]
+pub struct PruneOptions {
+    #[arg(long, short)]
+    skip_tests: bool,
+    /// only prune the given packages of the workspace
+    #[arg(long = "package", short)]
+    packages: Vec<String>,
+    /// directory for all generated artifacts while checking features
+    #[arg(long)]
+    target_dir: Option<String>,
+    /// only print a summary per dependency - enabled automatically if stdout is not a terminal
+    #[arg(long, short)]
+    quiet: bool,
+    /// cargo binary used to check the features - defaults to `$CARGO` or `cargo`, always `cargo` with a toolchain
+    #[arg(long)]
+    cargo: Option<String>,
+    /// toolchain passed as `+<toolchain>` to cargo - e.g. `nightly`
+    #[arg(long)]
+    toolchain: Option<String>,
+    /// stop checking further dependencies after the given time - e.g. `30m` or `1h30m`
+    #[arg(long, value_parser = parse_time_limit)]
+    time_limit: Option<Duration>,
+    /// kill a single build or test after the given time - e.g. `10m`, the feature is kept
+    #[arg(long, value_parser = parse_check_timeout)]
+    check_timeout: Option<Duration>,
+    /// treat warnings as errors - features whose removal causes warnings (e.g. unused imports) are kept
+    #[arg(long)]
+    deny_warnings: bool,
+    /// arguments appended to each cargo build & test - e.g. `--features my-feature`
+    #[arg(long, allow_hyphen_values = true)]
+    check_args: Option<String>,
+    /// also check with features of your own packages - `all` or a comma separated list, e.g. `cli,serde`
+    ///
+    /// a feature is only removed if both checks pass - dependency features used behind your own features are kept
+    #[arg(long)]
+    own_features: Option<String>,
+}
+
+pub fn prune(mut document: Document, args: PruneArgs) -> Result<()> {
     let mut term = Term::stdout();
 
-    let mut enabled_features = get_enabled_features(&document);
+    let quiet = args.options.quiet || !term.is_term();
+    let (checker, enabled_features) = prepare(&document, args.options)?;
 
-    let base_ignored_features =
-        get_ignored_features("./", "workspace.cargo-features-manager.keep")?;
-    remove_ignored_features(&document, &base_ignored_features, &mut enabled_features)?;
+    if args.no_build {
+        return display_candidates(&document, &mut term, &enabled_features);
+    }
 
-    prune_features(
+    if !confirm_plan(&mut term, &checker, &enabled_features, args.yes)? {
+        return Ok(());
+    }
+
+    let result = prune_features(
         &mut document,
-        is_dry_run,
-        skip_tests,
+        args.dry_run,
+        quiet,
+        &checker,
         &mut term,
         enabled_features,
         known_features()?,
     )?;
 
+    let removals = get_removals(&document, &result.removable)?;
+
+    if let OutputFormat::Github = args.format {
+        clear_line(&term)?;
+        write!(
+            term,
+            "{}",
+            format_annotations(&document, &result.removable)?
+        )?;
+    } else if !removals.is_empty() {
+        clear_line(&term)?;
+        writeln!(term)?;
+        writeln!(
+            term,
+            "{}",
+            if args.dry_run {
+                "removable features:"
+            } else {
+                "removed features:"
+            }
+        )?;
+        writeln!(term)?;
+        write!(
+            term,
+            "{}",
+            format_removals(&removals, document.is_workspace())
+        )?;
+    }
+
+    if args.update_keep {
+        update_kept_features(&document, &mut term, result.required, args.yes)?;
+    }
+
+    let removable_count = result
+        .removable
+        .values()
+        .flat_map(|dependencies| dependencies.values())
+        .flatten()
+        .count();
+
+    if args.check && removable_count > 0 {
+        clear_line(&term)?;
+        writeln!(term)?;
+        writeln!(term, "{} features can be removed", removable_count)?;
+
+        exit(1);
+    }
+
+    Ok(())
+}
+
+/// prunes all features without applying them - then reports and applies the removals
+pub fn minimize(mut document: Document, args: MinimizeArgs) -> Result<()> {
+    let mut term = Term::stdout();
+
+    let quiet = args.options.quiet || !term.is_term();
+    let (checker, enabled_features) = prepare(&document, args.options)?;
+
+    if !confirm_plan(&mut term, &checker, &enabled_features, args.yes)? {
+        return Ok(());
+    }
+
+    let result = prune_features(
+        &mut document,
+        true,
+        quiet,
+        &checker,
+        &mut term,
+        enabled_features.clone(),
+        known_features()?,
+    )?;
+
+    // dependencies skipped because of the time limit were not tested
+    let mut tested = enabled_features;
+
+    for (package_name, dependency_name) in &result.skipped {
+        if let Some(dependencies) = tested.get_mut(package_name) {
+            dependencies.remove(dependency_name);
+        }
+    }
+
+    clear_line(&term)?;
+    writeln!(term)?;
+    display_summary(&mut term, &tested, &result.removable)?;
+
+    if result.removable.is_empty() {
+        return Ok(());
+    }
+
+    if !confirm_changes(&mut term, args.yes)? {
+        return Ok(());
+    }
+
+    for (package_name, dependencies) in result.removable {
+        for (dependency_name, features) in dependencies {
+            let dependency = document
+                .get_package_mut(&package_name)?
+                .get_dep_mut(&dependency_name)?;
+
+            for feature in features {
+                dependency.disable_feature(&feature)?;
+            }
+
+            save_dependency(&mut document, &package_name, &dependency_name)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// checks which dependencies compile without their default features - then reports the features replacing them & applies them
+pub fn defaults(mut document: Document, args: DefaultsArgs) -> Result<()> {
+    let mut term = Term::stdout();
+
+    let quiet = args.options.quiet || !term.is_term();
+    let packages = args.options.packages.clone();
+    let checker = get_checker(&document, args.options)?;
+
+    let mut plan = get_default_features(&document)?;
+
+    if !packages.is_empty() {
+        for package in &packages {
+            document.get_package(package)?;
+        }
+
+        plan.retain(|package, _| packages.contains(package));
+    }
+
+    if plan.is_empty() {
+        writeln!(
+            term,
+            "nothing to check - no dependency uses default features which are not kept"
+        )?;
+        return Ok(());
+    }
+
+    if !confirm_plan(&mut term, &checker, &plan, args.yes)? {
+        return Ok(());
+    }
+
+    let mut replacements = FeaturesToTest::new();
+
+    for (package_name, dependencies) in plan.iter().sorted_by_key(|(name, _)| *name) {
+        for (dependency_name, candidates) in dependencies.iter().sorted_by_key(|(name, _)| *name) {
+            if !quiet {
+                clear_line(&term)?;
+                write!(
+                    term,
+                    "checking the default features of {}...",
+                    dependency_name
+                )?;
+                term.flush()?;
+            }
+
+            let removable = find_removable_defaults(
+                &mut document,
+                &checker,
+                package_name,
+                dependency_name,
+                candidates,
+            )?;
+
+            if removable.is_empty() {
+                continue;
+            }
+
+            let dependency = document
+                .get_package(package_name)?
+                .get_dep(dependency_name)?;
+
+            replacements
+                .entry(package_name.to_string())
+                .or_default()
+                .insert(
+                    dependency_name.to_string(),
+                    get_defaults(dependency)
+                        .into_iter()
+                        .filter(|feature| !removable.contains(feature))
+                        .collect(),
+                );
+        }
+    }
+
+    clear_line(&term)?;
+
+    if replacements.is_empty() {
+        writeln!(term, "every dependency needs all of its default features")?;
+        return Ok(());
+    }
+
+    writeln!(term, "default features which can be disabled:")?;
+    writeln!(term)?;
+    write!(
+        term,
+        "{}",
+        format_replacements(
+            &get_replacement_labels(&document, &replacements)?,
+            document.is_workspace()
+        )
+    )?;
+
+    if !confirm_changes(&mut term, args.yes)? {
+        return Ok(());
+    }
+
+    for (package_name, dependencies) in replacements {
+        for (dependency_name, features) in dependencies {
+            let dependency = document
+                .get_package_mut(&package_name)?
+                .get_dep_mut(&dependency_name)?;
+
+            let features = [dependency.get_features_to_enable(), features].concat();
+            dependency.set_enabled_features(&features)?;
+
+            save_dependency(&mut document, &package_name, &dependency_name)?;
+        }
+    }
+
     Ok(())
 }
 
+/// the features enabled via `default` per dependency which uses its default features - known & kept features are skipped like in prune
+///
+/// members inherit `default-features` of their workspace dependencies - those are checked in `[workspace.dependencies]`
+fn get_default_features(document: &Document) -> Result<FeaturesToTest> {
+    let known_features = known_features()?;
+    let mut data = FeaturesToTest::new();
+
+    for package in document.get_packages() {
+        for dependency in package.get_deps() {
+            if dependency.is_own_features() || dependency.workspace || !dependency.can_use_default()
+            {
+                continue;
+            }
+
+            let known = known_features
+                .get(&dependency.name)
+                .cloned()
+                .unwrap_or_default();
+
+            // e.g. the defaults of clap only remove behaviour - the build never breaks without them
+            if known.iter().any(|feature| feature == "default") {
+                continue;
+            }
+
+            let mut defaults = get_defaults(dependency);
+
+            for feature in &known {
+                remove_feature(feature, &mut defaults, dependency);
+            }
+
+            if defaults.is_empty() {
+                continue;
+            }
+
+            data.entry(package.name.to_string())
+                .or_default()
+                .insert(dependency.get_name(), defaults);
+        }
+    }
+
+    remove_ignored_features(document, &mut data)?;
+
+    Ok(data)
+}
+
+/// the features enabled via `default` - only the ones the dependency has
+fn get_defaults(dependency: &Dependency) -> Vec<String> {
+    dependency
+        .get_feature("default")
+        .map(|default| {
+            default
+                .sub_features
+                .iter()
+                .filter(|sub_feature| dependency.get_feature(&sub_feature.name).is_some())
+                .map(|sub_feature| sub_feature.name.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// returns the candidates the dependency compiles without - checked one by one & then together like prune
+///
+/// the other default features stay enabled - the dependency is reset to its features afterwards
+fn find_removable_defaults(
+    document: &mut Document,
+    checker: &Checker,
+    package_name: &str,
+    dependency_name: &str,
+    candidates: &[String],
+) -> Result<Vec<String>> {
+    let package = document.get_package(package_name)?;
+    let dependency = package.get_dep(dependency_name)?;
+
+    let checker = checker.for_trust(get_prune_trust(document, package, dependency));
+
+    let defaults = get_defaults(dependency);
+    let original = dependency.get_own_enabled_features();
+    let explicit = dependency.get_features_to_enable();
+
+    // enables the explicit features & the default features which are not removed - `default` itself stays disabled
+    let mut check_without = |removed: &[String]| -> Result<Option<bool>> {
+        let features = explicit
+            .iter()
+            .chain(defaults.iter().filter(|feature| !removed.contains(feature)))
+            .cloned()
+            .collect_vec();
+
+        let dependency = document
+            .get_package_mut(package_name)?
+            .get_dep_mut(dependency_name)?;
+
+        dependency.set_enabled_features(&features)?;
+
+        // a removed feature can still be enabled by one of the others - checking it would prove nothing
+        if removed.iter().any(|feature| {
+            dependency
+                .get_feature(feature)
+                .map_or(false, |data| data.is_enabled())
+        }) {
+            return Ok(None);
+        }
+
+        save_dependency(document, package_name, dependency_name)?;
+
+        let compiles = checker.check()?;
+
+        if checker.take_timed_out() {
+            warn!(
+                "a check of the default features of {} timed out",
+                dependency_name
+            );
+        }
+
+        Ok(Some(compiles))
+    };
+
+    let mut removable = vec![];
+
+    for feature in candidates {
+        if check_without(&[feature.to_string()])? == Some(true) {
+            removable.push(feature.to_string());
+        }
+    }
+
+    let removable = verify_removals(&removable, |removed| {
+        Ok(check_without(removed)? == Some(true))
+    })?;
+
+    let dependency = document
+        .get_package_mut(package_name)?
+        .get_dep_mut(dependency_name)?;
+
+    dependency.set_enabled_features(&original)?;
+    save_dependency(document, package_name, dependency_name)?;
+
+    Ok(removable)
+}
+
+/// the features replacing the defaults per dependency label per package - e.g. `tokio (dev)`
+fn get_replacement_labels(document: &Document, replacements: &FeaturesToTest) -> Result<Removals> {
+    let mut labels = Removals::new();
+
+    for (package_name, dependencies) in replacements {
+        let package = document.get_package(package_name)?;
+
+        for (dependency_name, features) in dependencies {
+            labels.entry(package_name.to_string()).or_default().insert(
+                get_label(package.get_dep(dependency_name)?),
+                features.iter().sorted().cloned().collect(),
+            );
+        }
+    }
+
+    Ok(labels)
+}
+
+/// one `- crate: default-features = false, features = [...]` line per dependency - grouped by package in workspaces
+fn format_replacements(replacements: &Removals, group_by_package: bool) -> String {
+    let mut text = String::new();
+
+    for (package_name, dependencies) in replacements {
+        if group_by_package {
+            text.push_str(&format!("{}\n", package_name));
+        }
+
+        for (dependency, features) in dependencies {
+            text.push_str(&format!("- {}: default-features = false", dependency));
+
+            if !features.is_empty() {
+                text.push_str(&format!(
+                    ", features = [{}]",
+                    features
+                        .iter()
+                        .map(|feature| format!("\"{}\"", feature))
+                        .join(", ")
+                ));
+            }
+
+            text.push('\n');
+        }
+
+        if group_by_package {
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+fn prepare(document: &Document, options: PruneOptions) -> Result<(Checker, FeaturesToTest)> {
+    let mut enabled_features = get_enabled_features(document);
+
+    if !options.packages.is_empty() {
+        for package in &options.packages {
+            document.get_package(package)?;
+        }
+
+        enabled_features.retain(|package, _| options.packages.contains(package));
+    }
+
+    let checker = get_checker(document, options)?;
+
+    remove_ignored_features(document, &mut enabled_features)?;
+
+    Ok((checker, enabled_features))
+}
+
+/// the checker configured by the options & the config of the workspace
+fn get_checker(document: &Document, options: PruneOptions) -> Result<Checker> {
+    Ok(Checker {
+        skip_tests: options.skip_tests,
+        frozen: document.is_frozen(),
+        cargo: get_cargo(
+            options.cargo,
+            options.toolchain.as_deref(),
+            env::var("CARGO").ok(),
+        ),
+        toolchain: options.toolchain,
+        root_path: document.root_path().to_string(),
+        time_limit: options.time_limit,
+        check_timeout: options.check_timeout,
+        timed_out: Cell::new(false),
+        deny_warnings: options.deny_warnings,
+        // the checks run from the root - a relative `--target-dir` is meant from where prune was started
+        target_dir: match options.target_dir {
+            Some(target_dir) => Some(
+                env::current_dir()?
+                    .join(target_dir)
+                    .to_string_lossy()
+                    .to_string(),
+            ),
+            None => get_config_string(document.root_path(), "target-dir")?,
+        },
+        check_args: match options.check_args {
+            Some(check_args) => Some(check_args),
+            None => get_config_string(document.root_path(), "check-args")?,
+        }
+        .map(|check_args| check_args.split_whitespace().map(String::from).collect())
+        .unwrap_or_default(),
+        own_features: match options.own_features.as_deref().map(str::trim) {
+            None => vec![],
+            Some("all") => vec!["--all-features".to_string()],
+            Some(features) => vec!["--features".to_string(), features.to_string()],
+        },
+    })
+}
+
+/// cargo sets CARGO for subcommands - so the features are checked with the cargo which started us
+///
+/// that is the binary of the toolchain itself though - only the rustup proxy `cargo` understands `+<toolchain>`
+fn get_cargo(cargo: Option<String>, toolchain: Option<&str>, env_cargo: Option<String>) -> String {
+    cargo
+        .or_else(|| env_cargo.filter(|_| toolchain.is_none()))
+        .unwrap_or("cargo".to_string())
+}
+
+/// asks whether to apply the listed changes - without a terminal to ask they are only listed
+fn confirm_changes(term: &mut Term, skip_confirmation: bool) -> Result<bool> {
+    if skip_confirmation {
+        return Ok(true);
+    }
+
+    if !term.is_term() {
+        writeln!(
+            term,
+            "the changes were not applied - there is no terminal to confirm them, use --yes to apply them"
+        )?;
+        return Ok(false);
+    }
+
+    write!(term, "apply these changes? [y/N] ")?;
+
+    let answer = term.read_char()?;
+    writeln!(term)?;
+
+    Ok(answer.eq_ignore_ascii_case(&'y'))
+}
+
+/// prints how many features will be checked & an estimate based on a single check - then asks to continue
+///
+/// returns false if there is nothing to check
+fn confirm_plan(
+    term: &mut Term,
+    checker: &Checker,
+    features: &FeaturesToTest,
+    skip_confirmation: bool,
+) -> Result<bool> {
+    let dependency_count = features
+        .values()
+        .map(|dependencies| dependencies.len())
+        .sum::<usize>();
+    let feature_count = features
+        .values()
+        .flat_map(|dependencies| dependencies.values())
+        .flatten()
+        .count();
+
+    if feature_count == 0 {
+        writeln!(
+            term,
+            "no features to check - only default or kept features are enabled"
+        )?;
+        return Ok(false);
+    }
+
+    if term.is_term() {
+        write!(term, "running a first check to estimate the duration...")?;
+        term.flush()?;
+    }
+
+    let baseline = check_baseline(checker);
+
+    clear_line(term)?;
+
+    let duration = baseline?;
+
+    writeln!(
+        term,
+        "checking {} features of {} dependencies in {} packages - this takes about {} ({} per check)",
+        feature_count,
+        dependency_count,
+        features.len(),
+        format_duration(duration * feature_count as u32),
+        format_duration(duration)
+    )?;
+
+    if skip_confirmation || !term.is_term() {
+        return Ok(true);
+    }
+
+    write!(term, "continue? [y/N] ")?;
+
+    let answer = term.read_char()?;
+    writeln!(term)?;
+
+    Ok(answer.eq_ignore_ascii_case(&'y'))
+}
+
+/// checks the unchanged project once & returns how long it took - every removal would fail if this check already fails
+fn check_baseline(checker: &Checker) -> Result<Duration> {
+    let start = Instant::now();
+
+    if !checker.check()? {
+        if checker.take_timed_out() {
+            bail!(
+                "the first check took longer than the check timeout of {}s - increase --check-timeout",
+                checker.check_timeout.unwrap_or_default().as_secs()
+            );
+        }
+
+        bail!(
+            "the project {}{} with its current features{} - fix it before pruning, otherwise no feature could be removed",
+            if checker.deny_warnings {
+                "does not compile without warnings"
+            } else {
+                "does not compile"
+            },
+            if checker.skip_tests {
+                ""
+            } else {
+                " or its tests fail"
+            },
+            if checker.own_features.is_empty() {
+                String::new()
+            } else {
+                format!(" or with `{}`", checker.own_features.join(" "))
+            }
+        );
+    }
+
+    Ok(start.elapsed())
+}
+
+fn parse_time_limit(text: &str) -> std::result::Result<Duration, String> {
+    parse_duration(text, "--time-limit")
+}
+
+fn parse_check_timeout(text: &str) -> std::result::Result<Duration, String> {
+    parse_duration(text, "--check-timeout")
+}
+
+/// parses a duration like `90s`, `30m`, `1h30m` - a number without unit are seconds
+fn parse_duration(text: &str, flag: &str) -> std::result::Result<Duration, String> {
+    let mut seconds = 0;
+    let mut number = String::new();
+
+    for char in text.trim().chars() {
+        if char.is_ascii_digit() {
+            number.push(char);
+            continue;
+        }
+
+        let factor = match char {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(format!("unknown unit {} - use h, m or s", char)),
+        };
+
+        let value: u64 = number
+            .parse()
+            .map_err(|_| format!("missing number in front of {}", char))?;
+
+        seconds += value * factor;
+        number.clear();
+    }
+
+    if !number.is_empty() {
+        seconds += number
+            .parse::<u64>()
+            .map_err(|err| format!("could not parse {} - {}", text, err))?;
+    }
+
+    if seconds == 0 {
+        return Err(format!("{} has to be greater than 0", flag));
+    }
+
+    Ok(Duration::from_secs(seconds))
+}
+
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+
+    match seconds {
+        0 => "<1s".to_string(),
+        1..=59 => format!("{}s", seconds),
+        60..=3599 => format!("{}m {}s", seconds / 60, seconds % 60),
+        _ => format!("{}h {}m", seconds / 3600, seconds % 3600 / 60),
+    }
+}
+
+fn display_summary(
+    term: &mut Term,
+    tested: &FeaturesToTest,
+    removable: &FeaturesToTest,
+) -> Result<()> {
+    let rows = tested
+        .iter()
+        .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+        .flat_map(|(package_name, dependencies)| {
+            dependencies
+                .iter()
+                .filter(|(_, features)| features.is_empty().not())
+                .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+                .map(move |(dependency_name, features)| {
+                    let removed = removable
+                        .get(package_name)
+                        .and_then(|dependencies| dependencies.get(dependency_name))
+                        .cloned()
+                        .unwrap_or_default();
+
+                    (package_name, dependency_name, features.len(), removed)
+                })
+        })
+        .collect_vec();
+
+    let package_width = rows
+        .iter()
+        .map(|(package_name, ..)| measure_text_width(package_name))
+        .max()
+        .unwrap_or_default()
+        .max("package".len());
+    let dependency_width = rows
+        .iter()
+        .map(|(_, dependency_name, ..)| measure_text_width(dependency_name))
+        .max()
+        .unwrap_or_default()
+        .max("dependency".len());
+
+    writeln!(
+        term,
+        "{}  {}  removed",
+        pad_str("package", package_width, Alignment::Left, None),
+        pad_str("dependency", dependency_width, Alignment::Left, None),
+    )?;
+
+    for (package_name, dependency_name, tested_count, removed) in &rows {
+        writeln!(
+            term,
+            "{}  {}  {}/{} {}",
+            pad_str(package_name, package_width, Alignment::Left, None),
+            pad_str(dependency_name, dependency_width, Alignment::Left, None),
+            removed.len(),
+            tested_count,
+            style(
+                removed
+                    .iter()
+                    .map(|feature| format!("-{}", feature))
+                    .join(",")
+            )
+            .red()
+        )?;
+    }
+
+    let tested_count: usize = rows.iter().map(|(_, _, count, _)| count).sum();
+    let removed_count: usize = rows.iter().map(|(.., removed)| removed.len()).sum();
+
+    writeln!(term)?;
+    writeln!(
+        term,
+        "removed {} of {} tested features ({}%)",
+        removed_count,
+        tested_count,
+        (removed_count * 100).checked_div(tested_count).unwrap_or(0)
+    )?;
+
+    Ok(())
+}
+
+/// lists the features prune would check which are not enabled by default or required by another enabled feature
+///
+/// nothing is compiled - a listed feature may still be needed to compile
+fn display_candidates(
+    document: &Document,
+    term: &mut Term,
+    enabled_features: &FeaturesToTest,
+) -> Result<()> {
+    let known_features = known_features()?;
+    let mut candidates = FeaturesToTest::new();
+
+    for (package_name, dependencies) in enabled_features {
+        let package = document.get_package(package_name)?;
+
+        for (dependency_name, features) in dependencies {
+            let dependency = package.get_dep(dependency_name)?;
+            let known = known_features
+                .get(&dependency.name)
+                .map_or(&[][..], |features| features.as_slice());
+
+            let features = get_candidates(dependency, features, known);
+
+            if !features.is_empty() {
+                candidates
+                    .entry(package_name.to_string())
+                    .or_default()
+                    .insert(dependency_name.to_string(), features);
+            }
+        }
+    }
+
+    if candidates.is_empty() {
+        writeln!(
+            term,
+            "no candidates - every enabled feature is enabled by default, required by another feature or kept"
+        )?;
+        return Ok(());
+    }
+
+    writeln!(
+        term,
+        "unverified candidates - nothing was compiled, run prune without --no-build to check them:"
+    )?;
+    writeln!(term)?;
+    write!(
+        term,
+        "{}",
+        format_removals(
+            &get_removals(document, &candidates)?,
+            document.is_workspace()
+        )
+    )?;
+
+    Ok(())
+}
+
+/// the features which are neither enabled by default nor a sub feature of another enabled feature - known features are skipped
+fn get_candidates(dependency: &Dependency, features: &[String], known: &[String]) -> Vec<String> {
+    let required = dependency.get_features_required_by_enabled();
+    let uses_default = dependency.can_use_default();
+
+    features
+        .iter()
+        .filter(|feature| *feature != "default" && !known.contains(feature))
+        .filter(|feature| !required.contains(feature.as_str()))
+        .filter(|feature| {
+            !(uses_default
+                && dependency
+                    .get_feature(feature)
+                    .map_or(false, |data| data.is_default))
+        })
+        .cloned()
+        .collect()
+}
+
+/// features per dependency label per package - sorted for a stable output
+type Removals = BTreeMap<String, BTreeMap<String, Vec<String>>>;
+
+/// returns the removable features with the plain names of the dependencies - e.g. `tokio (dev)`
+fn get_removals(document: &Document, removable: &FeaturesToTest) -> Result<Removals> {
+    let mut removals = Removals::new();
+
+    for (package_name, dependencies) in removable {
+        let package = document.get_package(package_name)?;
+
+        for (dependency_name, features) in dependencies {
+            if features.is_empty() {
+                continue;
+            }
+
+            let dependency = package.get_dep(dependency_name)?;
+
+            removals
+                .entry(package_name.to_string())
+                .or_default()
+                .entry(get_label(dependency))
+                .or_default()
+                .extend(features.iter().sorted().cloned());
+        }
+    }
+
+    Ok(removals)
+}
+
+/// the plain name of the dependency with its kind & target - e.g. `tokio (dev)`
+fn get_label(dependency: &Dependency) -> String {
+    let mut label = dependency.name.to_string();
+
+    match dependency.kind {
+        DependencyType::Development => label.push_str(" (dev)"),
+        DependencyType::Build => label.push_str(" (build)"),
+        _ => {}
+    }
+
+    if let Some(target) = &dependency.target {
+        label.push_str(&format!(" ({})", target));
+    }
+
+    label
+}
+
+/// one `- crate: feature` line per removed feature - grouped by package in workspaces, e.g. for a commit message
+fn format_removals(removals: &Removals, group_by_package: bool) -> String {
+    let mut text = String::new();
+
+    for (package_name, dependencies) in removals {
+        if group_by_package {
+            text.push_str(&format!("{}\n", package_name));
+        }
+
+        for (dependency, features) in dependencies {
+            for feature in features {
+                text.push_str(&format!("- {}: {}\n", dependency, feature));
+            }
+        }
+
+        if group_by_package {
+            text.push('\n');
+        }
+    }
+
+    text
+}
+
+/// one GitHub Actions `::warning` per removable feature - pointing at the line of the dependency in its manifest
+fn format_annotations(document: &Document, removable: &FeaturesToTest) -> Result<String> {
+    let current_dir = env::current_dir()?;
+    let mut text = String::new();
+
+    for (package_name, dependencies) in removable.iter().sorted_by_key(|(name, _)| *name) {
+        let package = document.get_package(package_name)?;
+        let manifest = fs::read_to_string(&package.manifest_path)
+            .wrap_err_with(|| format!("could not read {}", package.manifest_path))?;
+
+        let file = Path::new(&package.manifest_path);
+        let file = file.strip_prefix(&current_dir).unwrap_or(file);
+
+        for (dependency_name, features) in dependencies.iter().sorted_by_key(|(name, _)| *name) {
+            let dependency = package.get_dep(dependency_name)?;
+
+            let mut properties = format!("file={}", escape_property(&file.to_string_lossy()));
+
+            if let Some(line) = find_dependency_line(
+                &manifest,
+                &get_path(&dependency.kind, &dependency.target),
+                dependency.rename.as_ref().unwrap_or(&dependency.name),
+            ) {
+                properties.push_str(&format!(",line={}", line));
+            }
+
+            for feature in features.iter().sorted() {
+                text.push_str(&format!(
+                    "::warning {}::unused feature '{}' on dependency '{}'\n",
+                    properties, feature, dependency.name
+                ));
+            }
+        }
+    }
+
+    Ok(text)
+}
+
+/// escapes the characters with a meaning in the properties of a workflow command
+fn escape_property(value: &str) -> String {
+    value
+        .replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+        .replace(':', "%3A")
+        .replace(',', "%2C")
+}
+
+/// returns the 1-based line of the dependency in the table - either `key = ...` or a `[table.key]` header
+fn find_dependency_line(manifest: &str, table_path: &str, key: &str) -> Option<usize> {
+    let normalize = |text: &str| text.replace([' ', '\t'], "").replace('"', "'");
+
+    let table_path = normalize(table_path);
+    let mut current_table = String::new();
+
+    for (index, line) in manifest.lines().enumerate() {
+        let line = line.trim();
+
+        if let Some(header) = line.strip_prefix('[') {
+            current_table = normalize(header.split(']').next().unwrap_or_default());
+
+            if current_table == format!("{}.{}", table_path, key) {
+                return Some(index + 1);
+            }
+
+            continue;
+        }
+
+        if current_table != table_path {
+            continue;
+        }
+
+        let Some(rest) = line.strip_prefix(key) else {
+            continue;
+        };
+
+        if rest.trim_start().starts_with('=') || rest.starts_with('.') {
+            return Some(index + 1);
+        }
+    }
+
+    None
+}
+
 //give a map of known features that do not affect completion but remove functionality
 pub fn known_features() -> Result<HashMap<String, Vec<String>>> {
     let file = include_str!("../Known-Features.toml");
@@ -62,6 +1121,17 @@ pub fn known_features() -> Result<HashMap<String, Vec<String>>> {
 
 type FeaturesToTest = HashMap<String, HashMap<String, Vec<String>>>;
 
+struct PruneResult {
+    /// features which are required to compile - the ones whose check timed out are unknown & left out
+    required: FeaturesToTest,
+    /// features which are not required to compile - excluding known features
+    removable: FeaturesToTest,
+    /// package & dependency names which were not checked because the time limit was reached
+    skipped: Vec<(String, String)>,
+    /// package & dependency names & what was checked when the check timeout was reached - kept as they were
+    timed_out: Vec<(String, String, String)>,
+}
+
 fn get_enabled_features(document: &Document) -> FeaturesToTest {
     let mut data = HashMap::new();
 
@@ -69,6 +1139,13 @@ fn get_enabled_features(document: &Document) -> FeaturesToTest {
         let mut package_data = HashMap::new();
 
         for dependency in package.get_deps() {
+            // only default features are enabled - they are never pruned
+            if dependency.is_own_features()
+                || (dependency.can_use_default() && dependency.get_features_to_enable().is_empty())
+            {
+                continue;
+            }
+
             let enabled_features = dependency
                 .features
                 .iter()
@@ -90,42 +1167,57 @@ fn get_enabled_features(document: &Document) -> FeaturesToTest {
     data
 }
 
+/// removes the features kept via `cargo-features-manager.keep` of the package & the workspace
 fn remove_ignored_features(
     document: &Document,
-    base_ignored: &HashMap<String, Vec<String>>,
     enabled_features: &mut FeaturesToTest,
 ) -> Result<()> {
-    for (package_name, dependencies) in enabled_features {
+    for (package_name, dependencies) in enabled_features.iter_mut() {
         let package = document.get_package(package_name)?;
 
-        let ignored_features = get_ignored_features(
-            package.manifest_path.trim_end_matches("/Cargo.toml"),
-            "cargo-features-manager.keep",
-        )?;
-
-        for (dependency_name, features) in dependencies {
+        for (dependency_name, features) in dependencies.iter_mut() {
             let dependency = package.get_dep(dependency_name)?;
 
             if dependency.can_use_default() {
                 features.push("default".to_string());
             }
 
-            for feature in ignored_features.get(&dependency.name).unwrap_or(&vec![]) {
-                remove_feature(feature, features, dependency);
-            }
-            for feature in base_ignored.get(&dependency.name).unwrap_or(&vec![]) {
-                remove_feature(feature, features, dependency);
+            for keep in [&package.keep, document.workspace_keep()] {
+                if let Some(entry) = keep.get(&dependency.name) {
+                    for feature in &entry.features {
+                        remove_feature(feature, features, dependency);
+                    }
+                }
             }
 
             if let Some(index) = features.iter().position(|name| name == "default") {
                 features.remove(index);
             }
+
+            if get_prune_trust(document, package, dependency) == PruneTrust::Never {
+                debug!("skipping {} - it is never pruned", dependency_name);
+                features.clear();
+            }
         }
+
+        dependencies.retain(|_, features| !features.is_empty());
     }
 
+    enabled_features.retain(|_, dependencies| !dependencies.is_empty());
+
     Ok(())
 }
 
+/// the strictest trust of the keep entries of the package & the workspace
+fn get_prune_trust(document: &Document, package: &Package, dependency: &Dependency) -> PruneTrust {
+    [&package.keep, document.workspace_keep()]
+        .iter()
+        .filter_map(|keep| keep.get(&dependency.name))
+        .map(|entry| entry.prune)
+        .max()
+        .unwrap_or_default()
+}
+
 fn remove_feature(feature: &String, features: &mut Vec<String>, dependency: &Dependency) {
     let index = features.iter().position(|name| name == feature);
 
@@ -145,11 +1237,21 @@ fn remove_feature(feature: &String, features: &mut Vec<String>, dependency: &Dep
 fn prune_features(
     document: &mut Document,
     is_dry_run: bool,
-    skip_tests: bool,
+    quiet: bool,
+    checker: &Checker,
     term: &mut Term,
     features: FeaturesToTest,
     known_features: HashMap<String, Vec<String>>,
-) -> Result<()> {
+) -> Result<PruneResult> {
+    let mut result = PruneResult {
+        required: FeaturesToTest::new(),
+        removable: FeaturesToTest::new(),
+        skipped: vec![],
+        timed_out: vec![],
+    };
+
+    let start = Instant::now();
+
     let feature_count = features
         .values()
         .flat_map(|dependencies| dependencies.values())
@@ -160,11 +1262,13 @@ fn prune_features(
 
     let mut checked_features_count = 0;
 
-    writeln!(
-        term,
-        "workspace [{}/{}]",
-        checked_features_count, feature_count
-    )?;
+    if !quiet {
+        writeln!(
+            term,
+            "workspace [{}/{}]",
+            checked_features_count, feature_count
+        )?;
+    }
 
     let mut offset_to_top = 1;
 
@@ -183,8 +1287,8 @@ fn prune_features(
         let mut package_checked_features_count = 0;
         let mut package_offset_to_top = 1;
 
-        if document.is_workspace() {
-            term.clear_line()?;
+        if document.is_workspace() && !quiet {
+            clear_line(term)?;
             writeln!(term)?;
             writeln!(
                 term,
@@ -202,12 +1306,21 @@ fn prune_features(
                 continue;
             }
 
+            // a dependency is only skipped before its first check - the manifest is never left half pruned
+            if checker.is_out_of_time(start) {
+                result
+                    .skipped
+                    .push((package_name.clone(), dependency_name.clone()));
+                continue;
+            }
+
             let mut known_features_list = vec![];
-            let dependency = document
-                .get_package(&package_name)?
-                .get_dep(&dependency_name)?;
+            let package = document.get_package(&package_name)?;
+            let dependency = package.get_dep(&dependency_name)?;
+
+            let checker = checker.for_trust(get_prune_trust(document, package, dependency));
 
-            for feature_name in known_features.get(&dependency_name).unwrap_or(&vec![]) {
+            for feature_name in known_features.get(&dependency.name).unwrap_or(&vec![]) {
                 set_features_to_be_keept(
                     dependency,
                     feature_name.to_string(),
@@ -218,20 +1331,25 @@ fn prune_features(
             let mut to_be_disabled = vec![];
             to_be_disabled.append(&mut known_features_list.clone());
 
+            // a timed out check proves neither - these may still be removable
+            let mut timed_out = vec![];
+
             for (id, feature) in features.iter().enumerate() {
-                term.clear_line()?;
-                writeln!(
-                    term,
-                    "{:dependency_inset$}{} [{}/{}]",
-                    "",
-                    dependency_name,
-                    id,
-                    features.len()
-                )?;
-                term.clear_line()?;
-                writeln!(term, "{:dependency_inset$} └ {}", "", feature)?;
+                if !quiet {
+                    clear_line(term)?;
+                    writeln!(
+                        term,
+                        "{:dependency_inset$}{} [{}/{}]",
+                        "",
+                        dependency_name,
+                        id,
+                        features.len()
+                    )?;
+                    clear_line(term)?;
+                    writeln!(term, "{:dependency_inset$} └ {}", "", feature)?;
 
-                term.move_cursor_up(2)?;
+                    term.move_cursor_up(2)?;
+                }
 
                 document
                     .get_package_mut(&package_name)?
@@ -240,7 +1358,7 @@ fn prune_features(
 
                 save_dependency(document, &package_name, &dependency_name)?;
 
-                if !to_be_disabled.contains(feature) && check(skip_tests)? {
+                if !to_be_disabled.contains(feature) && checker.check()? {
                     set_features_to_be_disabled(
                         document
                             .get_package(&package_name)?
@@ -250,6 +1368,15 @@ fn prune_features(
                     );
                 }
 
+                if checker.take_timed_out() {
+                    timed_out.push(feature.to_string());
+                    result.timed_out.push((
+                        package_name.clone(),
+                        dependency_name.clone(),
+                        feature.to_string(),
+                    ));
+                }
+
                 //reset to start
                 for feature in &features {
                     document
@@ -263,6 +1390,10 @@ fn prune_features(
                 checked_features_count += 1;
                 package_checked_features_count += 1;
 
+                if quiet {
+                    continue;
+                }
+
                 term.move_cursor_up(offset_to_top)?;
                 writeln!(
                     term,
@@ -282,6 +1413,80 @@ fn prune_features(
                 }
             }
 
+            let candidates = to_be_disabled
+                .iter()
+                .filter(|feature| !known_features_list.contains(feature))
+                .cloned()
+                .collect_vec();
+
+            if candidates.len() > 1 {
+                if !quiet {
+                    clear_line(term)?;
+                    writeln!(
+                        term,
+                        "{:dependency_inset$}{} [{}/{}]",
+                        "",
+                        dependency_name,
+                        features.len(),
+                        features.len()
+                    )?;
+                    clear_line(term)?;
+                    writeln!(
+                        term,
+                        "{:dependency_inset$} └ checking the removals together",
+                        ""
+                    )?;
+
+                    term.move_cursor_up(2)?;
+                }
+
+                let verified = verify_removals(&candidates, |removed| {
+                    let dependency = document
+                        .get_package_mut(&package_name)?
+                        .get_dep_mut(&dependency_name)?;
+
+                    for feature in &features {
+                        dependency.enable_feature(feature)?;
+                    }
+
+                    for feature in removed {
+                        dependency.disable_feature(feature)?;
+                    }
+
+                    save_dependency(document, &package_name, &dependency_name)?;
+
+                    checker.check()
+                })?;
+
+                if checker.take_timed_out() {
+                    timed_out.extend(
+                        candidates
+                            .iter()
+                            .filter(|feature| !verified.contains(feature))
+                            .cloned(),
+                    );
+                    result.timed_out.push((
+                        package_name.clone(),
+                        dependency_name.clone(),
+                        "the removals together".to_string(),
+                    ));
+                }
+
+                to_be_disabled.retain(|feature| {
+                    known_features_list.contains(feature) || verified.contains(feature)
+                });
+
+                //reset to start
+                for feature in &features {
+                    document
+                        .get_package_mut(&package_name)?
+                        .get_dep_mut(&dependency_name)?
+                        .enable_feature(feature)?;
+                }
+
+                save_dependency(document, &package_name, &dependency_name)?;
+            }
+
             offset_to_top += 1;
             package_offset_to_top += 1;
 
@@ -304,15 +1509,54 @@ fn prune_features(
                 disabled_count = style("0".to_string());
             }
 
-            term.clear_line()?;
-            writeln!(
-                term,
-                "{:dependency_inset$}{} [{}/{}]",
-                "",
-                dependency_name,
-                disabled_count,
-                features.len()
-            )?;
+            if quiet {
+                let name = if document.is_workspace() {
+                    format!("{} {}", package_name, dependency_name)
+                } else {
+                    dependency_name.to_string()
+                };
+
+                writeln!(term, "{} [{}/{}]", name, disabled_count, features.len())?;
+            } else {
+                clear_line(term)?;
+                writeln!(
+                    term,
+                    "{:dependency_inset$}{} [{}/{}]",
+                    "",
+                    dependency_name,
+                    disabled_count,
+                    features.len()
+                )?;
+            }
+
+            let required = features
+                .iter()
+                .filter(|feature| !to_be_disabled.contains(feature) && !timed_out.contains(feature))
+                .cloned()
+                .collect_vec();
+
+            let removable = features
+                .iter()
+                .filter(|feature| to_be_disabled.contains(feature))
+                .filter(|feature| !known_features_list.contains(feature))
+                .cloned()
+                .collect_vec();
+
+            if required.is_empty().not() {
+                result
+                    .required
+                    .entry(package_name.clone())
+                    .or_default()
+                    .insert(dependency_name.clone(), required);
+            }
+
+            if removable.is_empty().not() {
+                result
+                    .removable
+                    .entry(package_name.clone())
+                    .or_default()
+                    .insert(dependency_name.clone(), removable);
+            }
 
             if is_dry_run {
                 continue;
@@ -335,12 +1579,276 @@ fn prune_features(
         }
     }
 
+    if !result.skipped.is_empty() {
+        clear_line(term)?;
+        writeln!(term)?;
+        writeln!(
+            term,
+            "{}",
+            style(format!(
+                "time limit reached - {} {} not checked:",
+                result.skipped.len(),
+                if result.skipped.len() == 1 {
+                    "dependency was"
+                } else {
+                    "dependencies were"
+                }
+            ))
+            .yellow()
+        )?;
+
+        for (package_name, dependency_name) in &result.skipped {
+            if document.is_workspace() {
+                writeln!(term, "  {} {}", package_name, dependency_name)?;
+            } else {
+                writeln!(term, "  {}", dependency_name)?;
+            }
+        }
+    }
+
+    if !result.timed_out.is_empty() {
+        clear_line(term)?;
+        writeln!(term)?;
+        writeln!(
+            term,
+            "{}",
+            style(format!(
+                "check timeout reached - {} {} killed, the features were kept and may still be removable:",
+                result.timed_out.len(),
+                if result.timed_out.len() == 1 {
+                    "check was"
+                } else {
+                    "checks were"
+                }
+            ))
+            .yellow()
+        )?;
+
+        for (package_name, dependency_name, checked) in &result.timed_out {
+            if document.is_workspace() {
+                writeln!(term, "  {} {} - {}", package_name, dependency_name, checked)?;
+            } else {
+                writeln!(term, "  {} - {}", dependency_name, checked)?;
+            }
+        }
+    }
+
     if has_known_features_enabled {
-        term.clear_line()?;
+        clear_line(term)?;
         writeln!(term)?;
         writeln!(term, "Some features that do not affect compilation but can limit functionally where found. For more information refer to https://github.com/ToBinio/cargo-features-manager?tab=readme-ov-file#prune")?;
     }
 
+    Ok(result)
+}
+
+/// returns the features which can be removed together - each one was only checked on its own
+///
+/// removing two features at once can still fail (e.g. both provide the same item) - then features are added back one by
+/// one until the rest compiles
+fn verify_removals(
+    removable: &[String],
+    mut compiles: impl FnMut(&[String]) -> Result<bool>,
+) -> Result<Vec<String>> {
+    let mut removed = removable.to_vec();
+
+    while !removed.is_empty() {
+        if compiles(&removed)? {
+            break;
+        }
+
+        removed.remove(0);
+    }
+
+    Ok(removed)
+}
+
+fn update_kept_features(
+    document: &Document,
+    term: &mut Term,
+    required_features: FeaturesToTest,
+    skip_confirmation: bool,
+) -> Result<()> {
+    let keep_file = get_config_string(document.root_path(), "keep-file")?;
+
+    for (package_name, dependencies) in required_features
+        .into_iter()
+        .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+    {
+        let package = document.get_package(&package_name)?;
+
+        // the entries of the workspace dependencies are only read from the workspace section
+        let is_workspace = document
+            .get_packages()
+            .iter()
+            .position(|other| other.name == package_name)
+            == document.workspace_index();
+        let table_path = if is_workspace {
+            WORKSPACE_KEEP
+        } else {
+            PACKAGE_KEEP
+        };
+
+        // the keep-file applies to every package - only the entries of the workspace or a single package go there
+        let (path, table_path) = match &keep_file {
+            Some(keep_file) if is_workspace || !document.is_workspace() => {
+                (Path::new(document.root_path()).join(keep_file), &[][..])
+            }
+            _ => (PathBuf::from(&package.manifest_path), table_path),
+        };
+        let destination = if table_path.is_empty() {
+            path.display().to_string()
+        } else {
+            format!("{} in {}", table_path.join("."), path.display())
+        };
+
+        clear_line(term)?;
+        writeln!(term)?;
+        writeln!(term, "{} requires:", package_name)?;
+
+        let mut kept_features = vec![];
+
+        for (dependency_name, features) in dependencies
+            .into_iter()
+            .sorted_by(|(name_a, _), (name_b, _)| name_a.cmp(name_b))
+        {
+            writeln!(term, "  {} [{}]", dependency_name, features.join(","))?;
+
+            kept_features.push((package.get_dep(&dependency_name)?.name.clone(), features));
+        }
+
+        // like starting the checks - there is no terminal to ask e.g. in CI
+        if !skip_confirmation && term.is_term() {
+            write!(term, "add to {}? [y/N] ", destination)?;
+
+            let answer = term.read_char()?;
+            writeln!(term)?;
+
+            if !answer.eq_ignore_ascii_case(&'y') {
+                continue;
+            }
+        }
+
+        let mut manifest = toml_document_from_path(&path)?;
+
+        add_kept_features(&mut manifest, table_path, kept_features)?;
+
+        fs::write(&path, manifest.to_string())?;
+        writeln!(term, "added to {}", destination)?;
+    }
+
+    Ok(())
+}
+
+/// the keep section of a package
+const PACKAGE_KEEP: &[&str] = &["cargo-features-manager", "keep"];
+
+/// the keep section of the workspace dependencies - in the root manifest
+const WORKSPACE_KEEP: &[&str] = &["workspace", "cargo-features-manager", "keep"];
+
+/// adds the features to the keep section at the path - only the entries are changed so comments & formatting survive
+fn add_kept_features(
+    manifest: &mut DocumentMut,
+    table_path: &[&str],
+    kept_features: Vec<(String, Vec<String>)>,
+) -> Result<()> {
+    let mut keep: &mut dyn TableLike = manifest.as_table_mut();
+
+    for (index, key) in table_path.iter().enumerate() {
+        // only the keep section itself gets a header - e.g. no empty `[cargo-features-manager]`
+        let table = if index + 1 == table_path.len() {
+            toml_edit::table()
+        } else {
+            implicit_table()
+        };
+
+        keep = keep
+            .entry(key)
+            .or_insert(table)
+            .as_table_like_mut()
+            .context(format!(
+                "could not parse {} as a table",
+                table_path[..=index].join(".")
+            ))?;
+    }
+
+    for (dependency_name, features) in kept_features {
+        let entry = keep
+            .entry(&dependency_name)
+            .or_insert(Item::Value(Value::Array(Array::new())));
+
+        // entries with notes keep their features in `keep`
+        let entry = match entry.as_table_like_mut() {
+            Some(table) => table
+                .entry("keep")
+                .or_insert(Item::Value(Value::Array(Array::new()))),
+            None => entry,
+        };
+
+        let kept = entry.as_array_mut().context(format!(
+            "could not parse {} as an array",
+            [table_path, &[dependency_name.as_str()]].concat().join(".")
+        ))?;
+
+        for feature in features {
+            if !kept.iter().any(|kept| kept.as_str() == Some(&feature)) {
+                push_formatted(kept, feature);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// pushes the value on its own line if the array spans multiple lines - a comment after the last value stays on its line
+fn push_formatted(array: &mut Array, value: String) {
+    let indent = array
+        .iter()
+        .last()
+        .and_then(|last| last.decor().prefix())
+        .and_then(|prefix| prefix.as_str())
+        .and_then(|prefix| prefix.rsplit_once('\n'))
+        .map(|(_, indent)| indent.to_string());
+
+    let Some(indent) = indent else {
+        array.push(value);
+        return;
+    };
+
+    // without a trailing comma the text in front of `]` is part of the last value
+    let mut trailing = String::new();
+
+    if !array.trailing_comma() {
+        if let Some(last) = array.iter_mut().last() {
+            trailing.push_str(
+                last.decor()
+                    .suffix()
+                    .and_then(|suffix| suffix.as_str())
+                    .unwrap_or_default(),
+            );
+            last.decor_mut().set_suffix("");
+        }
+    }
+
+    trailing.push_str(array.trailing().as_str().unwrap_or_default());
+
+    // everything in front of the line of `]` - e.g. a comment after the last value
+    let comment = trailing
+        .rsplit_once('\n')
+        .map(|(comment, _)| comment)
+        .unwrap_or_default();
+
+    array.push_formatted(Value::from(value).decorated(format!("{}\n{}", comment, indent), ""));
+    array.set_trailing("\n");
+    array.set_trailing_comma(true);
+}
+
+/// clears the current line - escape sequences are skipped if stdout is not a terminal
+fn clear_line(term: &Term) -> Result<()> {
+    if term.is_term() {
+        term.clear_line()?;
+    }
+
     Ok(())
 }
 
@@ -386,81 +1894,605 @@ fn set_features_to_be_keept(
     }
 }
 
-fn check(skip_tests: bool) -> Result<bool> {
-    if !build()? {
-        return Ok(false);
+#[derive(Clone)]
+struct Checker {
+    skip_tests: bool,
+    frozen: bool,
+    cargo: String,
+    toolchain: Option<String>,
+    /// the checks run from the workspace root - the same packages are built wherever prune is started
+    root_path: String,
+    target_dir: Option<String>,
+    /// no further dependencies are checked once this is exceeded
+    time_limit: Option<Duration>,
+    /// a single build or test is killed once this is exceeded - the check fails
+    check_timeout: Option<Duration>,
+    /// set once a build or test was killed - until `take_timed_out`
+    timed_out: Cell<bool>,
+    /// compile with `-D warnings` - a removal causing warnings fails the check
+    deny_warnings: bool,
+    /// appended after the arguments of each build & test
+    check_args: Vec<String>,
+    /// e.g. `--all-features` - each check is repeated with them
+    own_features: Vec<String>,
+}
+
+impl Checker {
+    fn is_out_of_time(&self, start: Instant) -> bool {
+        self.time_limit
+            .map_or(false, |time_limit| start.elapsed() >= time_limit)
     }
 
-    if !skip_tests && !test()? {
-        return Ok(false);
+    /// returns whether a build or test was killed since the last call
+    fn take_timed_out(&self) -> bool {
+        self.timed_out.replace(false)
     }
 
-    Ok(true)
-}
+    /// the checker for a dependency - `prune = "test"` runs the tests even with `--skip-tests`
+    fn for_trust(&self, trust: PruneTrust) -> Cow<'_, Checker> {
+        match trust {
+            PruneTrust::Test if self.skip_tests => Cow::Owned(Checker {
+                skip_tests: false,
+                ..self.clone()
+            }),
+            _ => Cow::Borrowed(self),
+        }
+    }
 
-fn build() -> Result<bool> {
-    let mut child = Command::new("cargo")
-        .arg("build")
-        .arg("--all-targets")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
+    fn check(&self) -> Result<bool> {
+        if !self.check_with(&[])? {
+            return Ok(false);
+        }
 
-    let code = child.wait()?.code().ok_or(eyre!("Could not build"))?;
+        if !self.own_features.is_empty() && !self.check_with(&self.own_features)? {
+            return Ok(false);
+        }
 
-    Ok(code == 0)
-}
+        Ok(true)
+    }
 
-fn test() -> Result<bool> {
-    let mut child = Command::new("cargo")
-        .arg("test")
-        .arg("--workspace")
-        .stdout(Stdio::null())
-        .stderr(Stdio::null())
-        .spawn()?;
+    fn check_with(&self, features: &[String]) -> Result<bool> {
+        if !self.build(features)? {
+            return Ok(false);
+        }
 
-    let code = child.wait()?.code().ok_or(eyre!("Could not test"))?;
+        if !self.skip_tests && !self.test(features)? {
+            return Ok(false);
+        }
 
-    Ok(code == 0)
-}
+        Ok(true)
+    }
 
-fn get_ignored_features<P: AsRef<Path>>(
-    file_path: P,
-    item_path: &str,
-) -> Result<HashMap<String, Vec<String>>> {
-    let result = toml_document_from_path(file_path.as_ref().join("Cargo.toml"));
+    fn build(&self, features: &[String]) -> Result<bool> {
+        let child = self
+            .command(
+                "build",
+                &[&["--all-targets".to_string()], features].concat(),
+            )
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .wrap_err_with(|| format!("could not run {}", self.cargo))?;
+
+        self.wait(child, "build")
+    }
 
-    match result {
-        Ok(document) => {
-            let item = get_item_from_doc(item_path, &document);
+    fn test(&self, features: &[String]) -> Result<bool> {
+        let child = self
+            .command("test", &[&["--workspace".to_string()], features].concat())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .wrap_err_with(|| format!("could not run {}", self.cargo))?;
 
-            let Ok(item) = item else {
-                return Ok(HashMap::new());
-            };
+        self.wait(child, "test")
+    }
 
-            let table = item.as_table_like().context(format!(
-                "could not parse {} in {:?}",
-                item_path,
-                file_path.as_ref()
-            ))?;
+    /// returns whether the child succeeded - it is killed & fails once the check timeout is exceeded
+    fn wait(&self, mut child: Child, action: &str) -> Result<bool> {
+        let start = Instant::now();
 
-            let mut map = HashMap::new();
+        let status = loop {
+            if let Some(status) = child.try_wait()? {
+                break status;
+            }
 
-            for (key, value) in table.iter() {
-                map.insert(
-                    key.to_string(),
-                    value
-                        .as_array()
-                        .ok_or(eyre!("Invalid format to keep features"))?
-                        .iter()
-                        .filter_map(|value| value.as_str())
-                        .map(|value| value.to_string())
-                        .collect(),
+            if self
+                .check_timeout
+                .map_or(false, |timeout| start.elapsed() >= timeout)
+            {
+                child.kill()?;
+                child.wait()?;
+                self.timed_out.set(true);
+
+                warn!(
+                    "{} killed after the check timeout of {:?}",
+                    action,
+                    start.elapsed()
                 );
+
+                return Ok(false);
             }
 
-            Ok(map)
+            thread::sleep(Duration::from_millis(50));
+        };
+
+        let code = status.code().ok_or(eyre!("Could not {}", action))?;
+
+        debug!(
+            "{} exited with {} after {:.1?}",
+            action,
+            code,
+            start.elapsed()
+        );
+
+        Ok(code == 0)
+    }
+
+    fn command(&self, subcommand: &str, args: &[String]) -> Command {
+        let mut command = Command::new(&self.cargo);
+
+        if let Some(toolchain) = &self.toolchain {
+            command.arg(format!("+{}", toolchain.trim_start_matches('+')));
+        }
+
+        command.arg(subcommand).current_dir(&self.root_path);
+
+        if self.frozen {
+            command.arg("--frozen");
+        }
+
+        if let Some(target_dir) = &self.target_dir {
+            command.arg("--target-dir").arg(target_dir);
+        }
+
+        if self.deny_warnings {
+            command.env("RUSTFLAGS", deny_warnings_flags(env::var("RUSTFLAGS").ok()));
+        }
+
+        command.args(args).args(&self.check_args);
+
+        debug!("running {:?}", command);
+
+        command
+    }
+}
+
+/// appends `-D warnings` to the rustflags already set - they would be replaced otherwise
+fn deny_warnings_flags(rustflags: Option<String>) -> String {
+    match rustflags {
+        Some(rustflags) if !rustflags.trim().is_empty() => {
+            format!("{} -D warnings", rustflags.trim())
+        }
+        _ => "-D warnings".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::{
+        add_kept_features, check_baseline, deny_warnings_flags, escape_property,
+        find_dependency_line, format_removals, format_replacements, get_candidates, get_cargo,
+        parse_check_timeout, parse_time_limit, verify_removals, Checker, PruneTrust, Removals,
+        PACKAGE_KEEP, WORKSPACE_KEEP,
+    };
+    use crate::project::dependency::builder::DependencyBuilder;
+    use std::cell::Cell;
+    use std::fs;
+    use std::path::Path;
+    use std::time::Duration;
+    use toml_edit::DocumentMut;
+
+    #[test]
+    fn adding_kept_features_keeps_comments() {
+        let mut manifest: DocumentMut = r#"[package]
+name = "a"
+
+# features prune can not detect
+[cargo-features-manager.keep]
+# used by the cli
+clap = [
+    "default", # help output
+]
+tokio = { keep = ["rt"], notes = { rt = "async runtime" } } # server
+anyhow = [
+    "std"
+]
+"#
+        .parse()
+        .unwrap();
+
+        add_kept_features(
+            &mut manifest,
+            PACKAGE_KEEP,
+            vec![
+                (
+                    "clap".to_string(),
+                    vec!["default".to_string(), "env".to_string()],
+                ),
+                ("tokio".to_string(), vec!["net".to_string()]),
+                ("serde".to_string(), vec!["derive".to_string()]),
+                ("anyhow".to_string(), vec!["backtrace".to_string()]),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.to_string(),
+            r#"[package]
+name = "a"
+
+# features prune can not detect
+[cargo-features-manager.keep]
+# used by the cli
+clap = [
+    "default", # help output
+    "env",
+]
+tokio = { keep = ["rt", "net"], notes = { rt = "async runtime" } } # server
+anyhow = [
+    "std",
+    "backtrace",
+]
+serde = ["derive"]
+"#
+        );
+    }
+
+    fn removals() -> Removals {
+        Removals::from([
+            (
+                "b".to_string(),
+                [("serde".to_string(), vec!["derive".to_string()])].into(),
+            ),
+            (
+                "a".to_string(),
+                [
+                    (
+                        "tokio".to_string(),
+                        vec!["net".to_string(), "rt".to_string()],
+                    ),
+                    ("clap (dev)".to_string(), vec!["color".to_string()]),
+                ]
+                .into(),
+            ),
+        ])
+    }
+
+    #[test]
+    fn lists_every_removed_feature() {
+        let removals = Removals::from([(
+            "a".to_string(),
+            [(
+                "tokio".to_string(),
+                vec!["net".to_string(), "rt".to_string()],
+            )]
+            .into(),
+        )]);
+
+        assert_eq!(
+            format_removals(&removals, false),
+            "- tokio: net\n- tokio: rt\n"
+        );
+    }
+
+    #[test]
+    fn groups_removed_features_by_package() {
+        assert_eq!(
+            format_removals(&removals(), true),
+            "a\n- clap (dev): color\n- tokio: net\n- tokio: rt\n\nb\n- serde: derive\n\n"
+        );
+    }
+
+    #[test]
+    fn lists_the_features_replacing_the_defaults() {
+        let replacements = Removals::from([(
+            "a".to_string(),
+            [
+                (
+                    "clap".to_string(),
+                    vec!["help".to_string(), "std".to_string()],
+                ),
+                ("toml_edit".to_string(), vec![]),
+            ]
+            .into(),
+        )]);
+
+        assert_eq!(
+            format_replacements(&replacements, false),
+            "- clap: default-features = false, features = [\"help\", \"std\"]\n- toml_edit: default-features = false\n"
+        );
+    }
+
+    #[test]
+    fn denying_warnings_keeps_the_existing_rustflags() {
+        assert_eq!(deny_warnings_flags(None), "-D warnings");
+        assert_eq!(deny_warnings_flags(Some(" ".to_string())), "-D warnings");
+        assert_eq!(
+            deny_warnings_flags(Some("-C target-cpu=native".to_string())),
+            "-C target-cpu=native -D warnings"
+        );
+    }
+
+    /// a checker running the given command instead of cargo - e.g. `false` to simulate a broken project
+    fn checker(cargo: &str) -> Checker {
+        Checker {
+            skip_tests: false,
+            frozen: false,
+            cargo: cargo.to_string(),
+            toolchain: None,
+            root_path: ".".to_string(),
+            target_dir: None,
+            time_limit: None,
+            check_timeout: None,
+            timed_out: Cell::new(false),
+            deny_warnings: false,
+            check_args: vec![],
+            own_features: vec![],
         }
-        Err(_) => Ok(HashMap::new()),
+    }
+
+    #[test]
+    fn a_toolchain_is_selected_via_the_rustup_proxy() {
+        let toolchain_cargo = Some("/toolchains/stable/bin/cargo".to_string());
+
+        assert_eq!(
+            get_cargo(None, None, toolchain_cargo.clone()),
+            "/toolchains/stable/bin/cargo"
+        );
+        assert_eq!(get_cargo(None, Some("nightly"), toolchain_cargo), "cargo");
+        assert_eq!(
+            get_cargo(Some("my-cargo".to_string()), Some("nightly"), None),
+            "my-cargo"
+        );
+    }
+
+    #[test]
+    fn dependencies_trusted_only_with_tests_are_always_tested() {
+        let mut checker = checker("cargo");
+        checker.skip_tests = true;
+
+        assert!(checker.for_trust(PruneTrust::Check).skip_tests);
+        assert!(!checker.for_trust(PruneTrust::Test).skip_tests);
+    }
+
+    #[test]
+    fn appends_the_check_args_after_the_own_args() {
+        let checker = Checker {
+            check_args: vec!["--features".to_string(), "local".to_string()],
+            ..checker("cargo")
+        };
+
+        let command = checker.command("build", &["--all-targets".to_string()]);
+
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            ["build", "--all-targets", "--features", "local"]
+        );
+    }
+
+    #[test]
+    fn candidates_skip_default_and_required_features() {
+        let dependency = DependencyBuilder::new("tokio")
+            .feature("default", &["macros"])
+            .feature("macros", &[])
+            .feature("full", &["rt", "fs"])
+            .feature("rt", &[])
+            .feature("fs", &[])
+            .feature("net", &[])
+            .feature("sync", &[])
+            .enabled(&["full", "rt", "net", "sync"])
+            .build()
+            .unwrap();
+
+        let features = ["macros", "full", "rt", "fs", "net", "sync"].map(String::from);
+
+        assert_eq!(
+            get_candidates(&dependency, &features, &["sync".to_string()]),
+            ["full", "net"]
+        );
+    }
+
+    #[test]
+    fn runs_the_checks_from_the_workspace_root() {
+        let checker = Checker {
+            root_path: "/path/to/workspace".to_string(),
+            ..checker("cargo")
+        };
+
+        let command = checker.command("build", &[]);
+
+        assert_eq!(
+            command.get_current_dir(),
+            Some(Path::new("/path/to/workspace"))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn aborts_if_the_project_does_not_compile() {
+        let err = check_baseline(&checker("false")).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "the project does not compile or its tests fail with its current features - fix it before pruning, otherwise no feature could be removed"
+        );
+
+        assert!(check_baseline(&checker("true")).is_ok());
+
+        let err = check_baseline(&Checker {
+            own_features: vec!["--all-features".to_string()],
+            ..checker("false")
+        })
+        .unwrap_err();
+
+        assert!(err
+            .to_string()
+            .contains("with its current features or with `--all-features` - "));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn kills_a_check_after_the_check_timeout() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let cargo = dir.path().join("cargo");
+        fs::write(&cargo, "#!/bin/sh\nsleep 10\n").unwrap();
+        fs::set_permissions(&cargo, fs::Permissions::from_mode(0o755)).unwrap();
+
+        let checker = Checker {
+            check_timeout: Some(Duration::from_millis(200)),
+            ..checker(cargo.to_str().unwrap())
+        };
+
+        assert!(!checker.check().unwrap());
+        assert!(checker.take_timed_out());
+        assert!(!checker.take_timed_out());
+    }
+
+    #[test]
+    fn keeps_features_which_are_only_removable_on_their_own() {
+        let features = ["a".to_string(), "b".to_string(), "c".to_string()];
+
+        // a & b provide the same item - either one can be removed, but not both
+        let mut checks = 0;
+        let removable = verify_removals(&features, |removed| {
+            checks += 1;
+            Ok(!(removed.contains(&features[0]) && removed.contains(&features[1])))
+        })
+        .unwrap();
+
+        assert_eq!(removable, ["b", "c"]);
+        assert_eq!(checks, 2);
+
+        let removable = verify_removals(&features, |_| Ok(true)).unwrap();
+        assert_eq!(removable, features);
+    }
+
+    #[test]
+    fn finds_the_line_of_a_dependency() {
+        let manifest = r#"[package]
+name = "a"
+
+[dependencies]
+serde = "1"
+tokio = { version = "1", features = ["rt"] }
+
+[dev-dependencies]
+tokio = "1"
+
+[target.'cfg(unix)'.dependencies]
+libc.version = "0.2"
+
+[build-dependencies.cc]
+version = "1"
+"#;
+
+        assert_eq!(
+            find_dependency_line(manifest, "dependencies", "tokio"),
+            Some(6)
+        );
+        assert_eq!(
+            find_dependency_line(manifest, "dev-dependencies", "tokio"),
+            Some(9)
+        );
+        assert_eq!(
+            find_dependency_line(manifest, "target.'cfg(unix)'.dependencies", "libc"),
+            Some(12)
+        );
+        assert_eq!(
+            find_dependency_line(manifest, "build-dependencies", "cc"),
+            Some(14)
+        );
+        assert_eq!(find_dependency_line(manifest, "dependencies", "ser"), None);
+    }
+
+    #[test]
+    fn escapes_annotation_properties() {
+        assert_eq!(
+            escape_property("crates/a,b/Cargo.toml"),
+            "crates/a%2Cb/Cargo.toml"
+        );
+        assert_eq!(escape_property("C:\\a\\Cargo.toml"), "C%3A\\a\\Cargo.toml");
+    }
+
+    #[test]
+    fn parses_durations() {
+        assert_eq!(parse_time_limit("90"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_time_limit("90s"), Ok(Duration::from_secs(90)));
+        assert_eq!(parse_time_limit("30m"), Ok(Duration::from_secs(1800)));
+        assert_eq!(parse_check_timeout("1h30m"), Ok(Duration::from_secs(5400)));
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        assert!(parse_time_limit("").is_err());
+        assert!(parse_time_limit("m").is_err());
+        assert!(parse_time_limit("5d").is_err());
+        assert_eq!(
+            parse_time_limit("0s"),
+            Err("--time-limit has to be greater than 0".to_string())
+        );
+        assert_eq!(
+            parse_check_timeout("0s"),
+            Err("--check-timeout has to be greater than 0".to_string())
+        );
+    }
+
+    #[test]
+    fn adding_kept_features_creates_the_section() {
+        let mut manifest: DocumentMut = "[package]\nname = \"a\"\n".parse().unwrap();
+
+        add_kept_features(
+            &mut manifest,
+            PACKAGE_KEEP,
+            vec![("clap".to_string(), vec!["env".to_string()])],
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.to_string(),
+            "[package]\nname = \"a\"\n\n[cargo-features-manager.keep]\nclap = [\"env\"]\n"
+        );
+    }
+
+    #[test]
+    fn kept_features_of_the_workspace_go_to_the_workspace_section() {
+        let mut manifest: DocumentMut = "[workspace]\nmembers = [\"a\"]\n".parse().unwrap();
+
+        add_kept_features(
+            &mut manifest,
+            WORKSPACE_KEEP,
+            vec![("clap".to_string(), vec!["env".to_string()])],
+        )
+        .unwrap();
+
+        assert_eq!(
+            manifest.to_string(),
+            "[workspace]\nmembers = [\"a\"]\n\n[workspace.cargo-features-manager.keep]\nclap = [\"env\"]\n"
+        );
+    }
+
+    #[test]
+    fn kept_features_of_the_keep_file_are_top_level_entries() {
+        let mut keep_file: DocumentMut = "serde = [\"derive\"] # macros\n".parse().unwrap();
+
+        add_kept_features(
+            &mut keep_file,
+            &[],
+            vec![
+                ("serde".to_string(), vec!["std".to_string()]),
+                ("clap".to_string(), vec!["env".to_string()]),
+            ],
+        )
+        .unwrap();
+
+        assert_eq!(
+            keep_file.to_string(),
+            "serde = [\"derive\", \"std\"] # macros\nclap = [\"env\"]\n"
+        );
     }
 }
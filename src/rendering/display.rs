@@ -1,12 +1,25 @@
-use crate::project::dependency::feature::EnabledState;
+use crate::cleanup::cleanup_package;
+use crate::parsing::package::get_resolved_metadata;
+use crate::parsing::transitive::{get_transitive_dependencies, TransitiveDependency};
+use crate::project::dependency::feature::{EnabledState, FeatureData, SubFeatureType};
+use crate::project::dependency::Dependency;
 use crate::project::document::Document;
-use crate::rendering::filter_view::FilterView;
-use crate::save::save_dependency;
-use color_eyre::eyre::{Context, ContextCompat};
-use color_eyre::Result;
-use console::{style, Emoji, Key, Term};
+use crate::rendering::filter_view::item::FilterViewItem;
+use crate::rendering::filter_view::{
+    DependencyKindFilter, FeatureSort, FeatureStateFilter, FeatureViewOptions, FilterView,
+};
+use crate::save::{add_dependency, render_dependency, save_dependency};
+use crate::util::{
+    copy_to_clipboard, get_features_activating_dependency, has_feature_named_like_dependency,
+};
+use color_eyre::eyre::{bail, Context, ContextCompat};
+use color_eyre::{Report, Result};
+use console::{measure_text_width, pad_str, style, truncate_str, Alignment, Emoji, Key, Term};
+use itertools::Itertools;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::io::Write;
 use std::ops::{Not, Range};
+use tracing::{debug, warn};
 
 pub struct Display {
     term: Term,
@@ -16,69 +29,191 @@ pub struct Display {
     package_selector: FilterView,
     dep_selector: FilterView,
     feature_selector: FilterView,
+    /// the features of every dependency - see `DisplayState::AllFeatures`
+    all_features_selector: FilterView,
+    /// the features of the compared dependencies - see `DisplayState::Compare`
+    compare_selector: FilterView,
+
+    feature_options: FeatureViewOptions,
+    dep_kind_filter: DependencyKindFilter,
+
+    /// toggling features & every other change of a manifest is disabled
+    read_only: bool,
+
+    /// the transitive dependencies per package - listed after the direct dependencies while set
+    transitive: Option<HashMap<String, Vec<TransitiveDependency>>>,
+
+    /// the transitive dependency selected once - selecting it again adds it to the manifest
+    pending_transitive: Option<String>,
+
+    /// the package & dependency picked with ctrl + p - picking a second one compares them
+    compare_pick: Option<(String, String)>,
+
+    /// the packages & dependencies shown side by side - left & right
+    compared: Option<[(String, String); 2]>,
+
+    /// the features marked with space & whether they will be enabled - applied together on enter
+    marked: Vec<(String, bool)>,
+
+    /// the last selected feature per package and dependency - restored when opening the dependency again
+    feature_positions: HashMap<(String, String), String>,
 
     state: DisplayState,
 
+    /// the view the search across all dependencies or the comparison was opened from - returned to on escape
+    previous_state: DisplayState,
+
     search_text: String,
+
+    /// the row number typed after `:` - the row is selected on enter
+    goto: Option<String>,
+
+    /// the meaning of the markers & colors of the current view below the rows - toggled with `?`
+    show_legend: bool,
+
+    notice: Option<String>,
+
+    /// an error the display recovered from - shown until the next input like the notice
+    error: Option<String>,
 }
 
 impl Display {
     pub fn new(document: Document) -> Result<Display> {
-        Ok(Display {
+        let load_errors = document.get_load_errors();
+
+        let notice = if !load_errors.is_empty() {
+            Some(format!(
+                "{} left out - {}",
+                if load_errors.len() == 1 {
+                    "1 dependency could not be parsed and is".to_string()
+                } else {
+                    format!(
+                        "{} dependencies could not be parsed and are",
+                        load_errors.len()
+                    )
+                },
+                load_errors[0]
+            ))
+        } else if !document.is_workspace() && document.get_package_by_id(0)?.dependencies.is_empty()
+        {
+            Some("no dependencies were found".to_string())
+        } else {
+            None
+        };
+
+        let mut display = Display {
             term: Term::buffered_stdout(),
-            package_selector: FilterView {
-                selected_index: 0,
-                data: FilterView::data_from_document(&document, "")?,
-            },
-            dep_selector: FilterView {
-                selected_index: 0,
-                data: FilterView::data_from_package(document.get_package_by_id(0)?, "")?,
-            },
-            feature_selector: FilterView {
-                selected_index: 0,
-                data: vec![],
-            },
+            package_selector: FilterView::new(FilterView::data_from_document(&document, "")?),
+            dep_selector: FilterView::new(FilterView::data_from_package(
+                document.get_package_by_id(0)?,
+                "",
+                DependencyKindFilter::default(),
+            )?),
+            feature_selector: FilterView::new(vec![]),
+            all_features_selector: FilterView::new(vec![]),
+            compare_selector: FilterView::new(vec![]),
+            feature_options: FeatureViewOptions::default(),
+            dep_kind_filter: DependencyKindFilter::default(),
+            read_only: false,
+            transitive: None,
+            pending_transitive: None,
+            compare_pick: None,
+            compared: None,
+            marked: vec![],
+            feature_positions: HashMap::new(),
             state: if document.is_workspace() {
                 DisplayState::Package
             } else {
                 DisplayState::Dep
             },
+            previous_state: DisplayState::Dep,
             search_text: "".to_string(),
+            goto: None,
+            show_legend: false,
+            notice,
+            error: None,
             document,
-        })
+        };
+
+        // a single dependency is opened right away - there is nothing to choose from
+        if let DisplayState::Dep = display.state {
+            if display.dep_selector.data.len() == 1
+                && display
+                    .document
+                    .get_package_by_id(0)?
+                    .get_dep(display.dep_selector.get_selected()?.name())?
+                    .has_features()
+            {
+                display.select_selected_dep()?;
+            }
+        }
+
+        Ok(display)
     }
 
     fn select_selected_package(&mut self) -> Result<()> {
         self.state = DisplayState::Dep;
+        self.package_selector.hidden_selection = None;
 
         // update selector
-        self.dep_selector.data = FilterView::data_from_package(
-            self.document
-                .get_package(self.package_selector.get_selected()?.name())?,
-            "",
-        )?;
+        self.dep_selector.data = self.dep_data(self.package_selector.get_selected()?.name(), "")?;
 
         Ok(())
     }
 
-    pub fn set_selected_dep(&mut self, dep_name: String) -> Result<()> {
-        match self
-            .document
-            .get_package(self.package_selector.get_selected()?.name())?
-            .get_dep_index(&dep_name)
-        {
-            Ok(index) => {
-                self.dep_selector.selected_index = index;
+    pub fn set_selected_package(&mut self, package_name: &str) -> Result<()> {
+        if !self.package_selector.select(package_name) {
+            bail!("package \"{}\" could not be found", package_name);
+        }
 
-                self.select_selected_dep()?;
-                Ok(())
+        self.select_selected_package()
+    }
+
+    /// without a package the first package with the dependency is selected in a workspace
+    pub fn set_selected_dep(&mut self, package_name: Option<&str>, dep_name: String) -> Result<()> {
+        if let Some(package_name) = package_name {
+            self.set_selected_package(package_name)?;
+        } else if self.document.is_workspace() {
+            let package_index = self.package_selector.data.iter().position(|item| {
+                self.document
+                    .get_package(item.name())
+                    .map_or(false, |package| package.get_dep_index(&dep_name).is_ok())
+            });
+
+            if let Some(package_index) = package_index {
+                self.package_selector.selected_index = package_index;
+                self.select_selected_package()?;
             }
-            Err(err) => Err(err),
         }
+
+        let package = self
+            .document
+            .get_package(self.package_selector.get_selected()?.name())?;
+
+        let dep = &package.dependencies[package.get_dep_index(&dep_name)?];
+
+        self.dep_selector.selected_index = self
+            .dep_selector
+            .data
+            .iter()
+            .position(|item| item.name() == dep.get_name())
+            .context(format!("could not select {}", dep.get_name()))?;
+
+        if dep.has_features() {
+            self.select_selected_dep()?;
+        } else {
+            self.state = DisplayState::Dep;
+            self.notice = Some(format!("{} has no features", dep.get_name()));
+        }
+
+        Ok(())
     }
 
     fn select_selected_dep(&mut self) -> Result<()> {
         self.state = DisplayState::Feature;
+        self.marked.clear();
+        self.dep_selector.hidden_selection = None;
+        self.feature_selector.hidden_selection = None;
 
         let dep = self
             .document
@@ -86,11 +221,25 @@ impl Display {
             .get_dep(self.dep_selector.get_selected()?.name())?;
 
         // update selector
-        self.feature_selector.data = FilterView::data_from_dependency(dep, &self.search_text);
+        self.feature_selector.data =
+            FilterView::data_from_dependency(dep, &self.search_text, &self.feature_options);
+
+        let position = self.feature_positions.get(&self.get_position_key()?);
+
+        if !position.map_or(false, |name| self.feature_selector.select(name)) {
+            self.feature_selector.selected_index = 0;
+        }
 
         Ok(())
     }
 
+    fn get_position_key(&self) -> Result<(String, String)> {
+        Ok((
+            self.package_selector.get_selected()?.name().to_string(),
+            self.dep_selector.get_selected()?.name().to_string(),
+        ))
+    }
+
     pub fn start(&mut self) -> Result<()> {
         //setup
         self.term.hide_cursor()?;
@@ -102,76 +251,165 @@ impl Display {
         self.term.move_cursor_to(0, 0)?;
         self.term.flush()?;
 
+        let result = self.run();
+
+        //always restore the cursor - even when exiting because of an error
+        self.term.show_cursor()?;
+        self.term.flush()?;
+
+        result
+    }
+
+    fn run(&mut self) -> Result<()> {
+        let mut has_recovered = false;
+
         loop {
-            match self.state {
-                DisplayState::Dep => self.display_deps()?,
-                DisplayState::Feature => self.display_features()?,
-                DisplayState::Package => self.display_packages()?,
+            match self.step() {
+                Ok(RunningState::Running) => has_recovered = false,
+                Ok(RunningState::Finished) => break,
+                // failing again right after recovering would loop forever
+                Err(err) if has_recovered => return Err(err),
+                Err(err) => {
+                    self.recover(err)?;
+                    has_recovered = true;
+                }
             }
+        }
 
-            self.term.flush()?;
+        Ok(())
+    }
 
-            //clear previous screen
-            self.term.clear_last_lines(self.term.size().0 as usize)?;
-            if let RunningState::Finished = self.input_event()? {
-                break;
-            }
+    fn step(&mut self) -> Result<RunningState> {
+        match self.state {
+            DisplayState::Dep => self.display_deps()?,
+            DisplayState::Feature => self.display_features()?,
+            DisplayState::Package => self.display_packages()?,
+            DisplayState::AllFeatures => self.display_all_features()?,
+            DisplayState::Compare => self.display_compare()?,
         }
 
-        self.term.show_cursor()?;
+        self.display_legend()?;
+        self.display_notice()?;
+
+        self.term.flush()?;
+
+        //clear previous screen
+        self.term.clear_last_lines(self.term.size().0 as usize)?;
+
+        self.input_event()
+    }
+
+    /// shows the error and returns to the dependencies of the selected package - or the packages if it is gone
+    ///
+    /// errors of the terminal itself can not be recovered from
+    fn recover(&mut self, err: Report) -> Result<()> {
+        if err.downcast_ref::<std::io::Error>().is_some() {
+            return Err(err);
+        }
+
+        // the failed render may have left a partial screen behind
         self.term.flush()?;
+        self.term.move_cursor_to(0, 0)?;
+        self.term.clear_to_end_of_screen()?;
+
+        self.error = Some(format!("{:#}", err));
+        self.search_text = "".to_string();
+
+        let package_name = self.package_selector.get_selected_name();
+
+        self.package_selector.data = FilterView::data_from_document(&self.document, "")?;
+        if !package_name.map_or(false, |name| self.package_selector.select(&name)) {
+            self.package_selector.selected_index = 0;
+        }
+
+        let package_name = self.package_selector.get_selected_name();
+
+        match package_name {
+            Some(name) if self.document.get_package(&name).is_ok() => {
+                let dep_name = self.dep_selector.get_selected_name();
+
+                self.dep_selector.data = self.dep_data(&name, "")?;
+                if !dep_name.map_or(false, |name| self.dep_selector.select(&name)) {
+                    self.dep_selector.shift(0);
+                }
+
+                self.state = DisplayState::Dep;
+            }
+            _ => self.state = DisplayState::Package,
+        }
 
         Ok(())
     }
 
     fn display_packages(&mut self) -> Result<()> {
-        write!(self.term, "Packages")?;
-        self.display_search_header()?;
+        self.display_header("Packages")?;
 
         let dep_range = self.get_max_range()?;
+        let layout = Layout::new(self.width());
 
-        let mut line_index = 1;
-        let mut index = dep_range.start;
+        for (line_index, index) in (1..).zip(dep_range.clone()) {
+            let selected = &self.package_selector.data[index];
 
-        for selected in &self.package_selector.data[dep_range] {
             if index == self.package_selector.selected_index {
                 self.term.move_cursor_to(0, line_index)?;
                 write!(self.term, ">")?;
             }
 
-            self.term.move_cursor_to(2, line_index)?;
-            write!(self.term, "{}", selected.display_name())?;
-
-            index += 1;
-            line_index += 1;
+            self.term.move_cursor_to(layout.name, line_index)?;
+            write!(
+                self.term,
+                "{}",
+                fit_to_width(selected.display_name(), layout.name_width)
+            )?;
         }
 
+        self.display_scrollbar(dep_range)?;
+
         Ok(())
     }
 
     fn display_deps(&mut self) -> Result<()> {
-        write!(self.term, "Dependencies")?;
-        self.display_search_header()?;
+        let mut header = "Dependencies".to_string();
+
+        if self.dep_kind_filter != DependencyKindFilter::All {
+            header.push_str(&format!(
+                " {}",
+                style(format!("[{}]", self.dep_kind_filter.label())).color256(8)
+            ));
+        }
+
+        if let Some((_, dep_name)) = &self.compare_pick {
+            header.push_str(&format!(
+                " {}",
+                style(format!("[picked {}]", dep_name)).color256(8)
+            ));
+        }
+
+        self.display_header(&header)?;
 
         let dep_range = self.get_max_range()?;
+        let layout = Layout::new(self.width());
 
-        let mut line_index = 1;
-        let mut index = dep_range.start;
+        for (line_index, index) in (1..).zip(dep_range.clone()) {
+            let selector = &self.dep_selector.data[index];
 
-        for selector in &self.dep_selector.data[dep_range] {
             if index == self.dep_selector.selected_index {
                 self.term.move_cursor_to(0, line_index)?;
                 write!(self.term, ">")?;
             }
 
-            self.term.move_cursor_to(2, line_index)?;
+            self.term.move_cursor_to(layout.name, line_index)?;
 
-            write!(self.term, "{}", selector.display_name())?;
-
-            index += 1;
-            line_index += 1;
+            // the delta to the defaults can make the row longer than the screen
+            write!(
+                self.term,
+                "{}",
+                fit_to_width(selector.display_name(), layout.name_width)
+            )?;
         }
 
+        self.display_scrollbar(dep_range)?;
+
         Ok(())
     }
 
@@ -188,11 +426,57 @@ impl Display {
         let feature_range = self.get_max_range()?;
 
         let mut line_index = 1;
-        let mut index = feature_range.start;
 
-        write!(self.term, "{} {}", dep.get_name(), dep.get_version())?;
+        let mut header = format!("{} {}", dep.get_name(), dep.get_version());
+
+        if let Some(resolved_version) = &dep.resolved_version {
+            // saving keeps the requirement - the resolved version is never written
+            let requirement = if dep.workspace {
+                "requirement of the workspace"
+            } else {
+                "requirement - kept on save"
+            };
+
+            header.push_str(&format!(
+                " {}",
+                style(format!("({}, resolved {})", requirement, resolved_version)).color256(8)
+            ));
+        }
+
+        if self.feature_options.state_filter != FeatureStateFilter::All {
+            header.push_str(&format!(
+                " {}",
+                style(format!("[{}]", self.feature_options.state_filter.label())).color256(8)
+            ));
+        }
+
+        if self.feature_options.sort != FeatureSort::DefaultsFirst {
+            header.push_str(&format!(
+                " {}",
+                style(format!("[{}]", self.feature_options.sort.label())).color256(8)
+            ));
+        }
+
+        if self.feature_options.explicit_only {
+            header.push_str(&format!(" {}", style("[explicit]").color256(8)));
+        }
+
+        if self.read_only {
+            header.push_str(&format!(" {}", style("[read-only]").color256(8)));
+        }
+
+        if self.document.is_scratch() {
+            header.push_str(&format!(" {}", style("[scratch]").color256(8)));
+        }
+
+        if !self.marked.is_empty() {
+            header.push_str(&format!(
+                " {}",
+                style(format!("[{} marked]", self.marked.len())).yellow()
+            ));
+        }
 
-        self.display_search_header()?;
+        self.display_header(&header)?;
 
         let dep = self
             .document
@@ -203,43 +487,88 @@ impl Display {
                 self.dep_selector.get_selected()?.name()
             ))?;
 
-        for feature in &self.feature_selector.data[self.get_max_range()?] {
+        let layout = Layout::new(self.width());
+
+        // computed once instead of per row - a dependency can have hundreds of features
+        let implied = dep.get_implied_features();
+        let required = dep.get_features_required_by_enabled();
+
+        for index in feature_range.clone() {
+            let feature = &self.feature_selector.data[index];
+
+            if feature.is_group() {
+                if index == self.feature_selector.selected_index {
+                    self.term.move_cursor_to(0, line_index)?;
+                    write!(self.term, ">")?;
+                }
+
+                self.term.move_cursor_to(layout.name, line_index)?;
+                write!(
+                    self.term,
+                    "{}",
+                    fit_to_width(feature.display_name(), layout.name_width)
+                )?;
+
+                line_index += 1;
+                continue;
+            }
+
             let data = dep
                 .get_feature(feature.name())
                 .context(format!("couldn't find {}", feature.name()))?;
 
-            self.term.move_cursor_to(2, line_index)?;
+            self.term.move_cursor_to(layout.name, line_index)?;
+
+            let is_implied = implied.contains(feature.name());
+
+            let is_marked = self.marked.iter().any(|(name, _)| name == feature.name());
 
             let marker = match data.enabled_state {
-                EnabledState::Normal(is_enabled) => {
-                    if is_enabled {
-                        "[X]".to_string()
-                    } else {
-                        "[ ]".to_string()
-                    }
-                }
-                EnabledState::Workspace => format!("{}", Emoji("🗃️", "W")),
+                EnabledState::Normal(_) if is_marked => "[~]".to_string(),
+                _ => get_marker(data, is_implied),
             };
 
-            if data.is_default {
+            // the emoji of workspace features is narrower than `[X]` - the names stay aligned
+            let marker = pad_str(&marker, MARKER_WIDTH, Alignment::Left, None);
+
+            if is_marked {
+                write!(self.term, "{}", style(marker).yellow())?;
+            } else if data.is_default {
                 write!(self.term, "{}", style(marker).green())?;
             } else {
                 write!(self.term, "{}", marker)?;
             }
 
+            let mut label = String::new();
+
+            if dep.is_optional_dependency(feature.name()) {
+                label.push_str(&Emoji("📦", "dep:").to_string());
+            }
+
             let mut feature_name = style(feature.display_name());
 
-            if !dep
-                .get_currently_dependent_features(feature.name())
-                .is_empty()
+            if required.contains(feature.name())
+                || is_implied
                 || data.enabled_state == EnabledState::Workspace
             {
                 //gray
                 feature_name = feature_name.color256(8);
             }
 
-            self.term.move_cursor_right(1)?;
-            write!(self.term, "{}", feature_name)?;
+            label.push_str(&feature_name.to_string());
+
+            if data.is_custom {
+                label.push_str(&style(" (custom)").color256(8).to_string());
+            }
+
+            // long names would wrap into the next row - which is overwritten by the sub features
+            self.term
+                .move_cursor_to(layout.name + MARKER_WIDTH + 1, line_index)?;
+            write!(
+                self.term,
+                "{}",
+                fit_to_width(&label, layout.name_width.saturating_sub(MARKER_WIDTH + 1))
+            )?;
 
             if index == self.feature_selector.selected_index {
                 self.term.move_cursor_to(0, line_index)?;
@@ -250,209 +579,1016 @@ impl Display {
                 if sub_features.is_empty().not() {
                     line_index += 1;
 
-                    self.term.move_cursor_to(6, line_index)?;
+                    self.term.move_cursor_to(layout.sub_features, line_index)?;
                     write!(self.term, "└")?;
 
-                    self.term.move_cursor_to(8, line_index)?;
+                    self.term
+                        .move_cursor_to(layout.sub_features + 2, line_index)?;
+
+                    let sub_features = sub_features
+                        .iter()
+                        .map(|sub_feature| {
+                            let name = sub_feature.name.trim_start_matches("dep:");
+
+                            // e.g. `serde/std` is a feature of another crate - it has no state here
+                            let Some(data) = dep
+                                .get_feature(name)
+                                .filter(|_| sub_feature.kind != SubFeatureType::DependencyFeature)
+                            else {
+                                return sub_feature.to_string();
+                            };
+
+                            let marker = get_marker(data, implied.contains(name));
+
+                            if data.is_default {
+                                format!("{} {}", style(marker).green(), sub_feature)
+                            } else {
+                                format!("{} {}", marker, sub_feature)
+                            }
+                        })
+                        .join("  ");
+                    write!(
+                        self.term,
+                        "{}",
+                        fit_to_width(&sub_features, layout.sub_features_width.saturating_sub(2))
+                    )?;
+                }
+
+                if let Some(note) = &data.note {
+                    line_index += 1;
 
-                    for sub in sub_features {
-                        write!(self.term, "{} ", sub)?;
-                    }
+                    self.term.move_cursor_to(layout.sub_features, line_index)?;
+                    write!(
+                        self.term,
+                        "{}",
+                        style(fit_to_width(
+                            &format!("note: {}", note),
+                            layout.sub_features_width
+                        ))
+                        .color256(8)
+                    )?;
                 }
             }
 
             line_index += 1;
-            index += 1;
         }
 
+        self.display_scrollbar(feature_range)?;
+
         Ok(())
     }
 
-    fn display_search_header(&mut self) -> Result<()> {
-        if !self.search_text.is_empty() {
-            write!(self.term, " - {}", self.search_text)?;
+    /// the markers are looked up in the document - the items only know where their feature is
+    fn display_all_features(&mut self) -> Result<()> {
+        let mut header = "Features of all dependencies".to_string();
+
+        if self.document.is_workspace() {
+            header.push_str(&format!(" {}", style("[all packages]").color256(8)));
         }
 
-        Ok(())
-    }
+        self.display_header(&header)?;
 
-    fn input_event(&mut self) -> Result<RunningState> {
-        match (self.term.read_key()?, &self.state) {
-            //movement
-            //up
-            (Key::ArrowUp, DisplayState::Package) => {
-                self.package_selector.shift(-1);
-            }
-            (Key::ArrowUp, DisplayState::Dep) => {
-                self.dep_selector.shift(-1);
-            }
-            (Key::ArrowUp, DisplayState::Feature) => {
-                if self.feature_selector.has_data() {
-                    self.feature_selector.shift(-1);
-                }
-            }
-            //down
-            (Key::ArrowDown, DisplayState::Package) => {
-                self.package_selector.shift(1);
-            }
-            (Key::ArrowDown, DisplayState::Dep) => {
-                self.dep_selector.shift(1);
-            }
-            (Key::ArrowDown, DisplayState::Feature) => {
-                if self.feature_selector.has_data() {
-                    self.feature_selector.shift(1);
-                }
-            }
+        let feature_range = self.get_max_range()?;
+        let layout = Layout::new(self.width());
 
-            //selection
-            (Key::Enter, DisplayState::Package)
-            | (Key::ArrowRight, DisplayState::Package)
-            | (Key::Char(' '), DisplayState::Package) => {
-                if self.package_selector.has_data() {
-                    let name = self.package_selector.get_selected()?.name();
+        for (line_index, index) in (1..).zip(feature_range.clone()) {
+            let item = &self.all_features_selector.data[index];
+            let location = item.location().context("not a feature of a dependency")?;
 
-                    if !self
-                        .document
-                        .get_package(name)
-                        .context(format!("package not found - {}", name))?
-                        .dependencies
-                        .is_empty()
-                    {
-                        self.search_text = "".to_string();
+            let dep = self
+                .document
+                .get_package(&location.package)?
+                .get_dep(&location.dependency)?;
+            let data = dep
+                .get_feature(&location.feature)
+                .context(format!("couldn't find {}", location.feature))?;
 
-                        self.select_selected_package()?;
+            let marker = get_marker(data, dep.get_implied_features().contains(&location.feature));
+            let marker = pad_str(&marker, MARKER_WIDTH, Alignment::Left, None);
 
-                        //needed to wrap
-                        self.dep_selector.shift(0);
-                    }
-                }
+            if index == self.all_features_selector.selected_index {
+                self.term.move_cursor_to(0, line_index)?;
+                write!(self.term, ">")?;
             }
-            (Key::Enter, DisplayState::Dep)
-            | (Key::ArrowRight, DisplayState::Dep)
-            | (Key::Char(' '), DisplayState::Dep) => {
-                if self.dep_selector.has_data()
-                    && self
-                        .document
-                        .get_package(self.package_selector.get_selected()?.name())?
-                        .get_dep(self.dep_selector.get_selected()?.name())?
-                        .has_features()
-                {
-                    self.search_text = "".to_string();
 
-                    self.select_selected_dep()?;
+            self.term.move_cursor_to(layout.name, line_index)?;
 
-                    //needed to wrap
-                    self.feature_selector.shift(0);
-                }
+            if data.is_default {
+                write!(self.term, "{}", style(marker).green())?;
+            } else {
+                write!(self.term, "{}", marker)?;
             }
-            (Key::Enter, DisplayState::Feature)
-            | (Key::ArrowRight, DisplayState::Feature)
-            | (Key::Char(' '), DisplayState::Feature) => {
-                if self.feature_selector.has_data() {
-                    let dep_name = self.dep_selector.get_selected()?.name();
 
-                    let dep = self
-                        .document
-                        .get_package_mut(self.package_selector.get_selected()?.name())?
-                        .get_dep_mut(dep_name)?;
+            self.term
+                .move_cursor_to(layout.name + MARKER_WIDTH + 1, line_index)?;
+            write!(
+                self.term,
+                "{}",
+                fit_to_width(
+                    item.display_name(),
+                    layout.name_width.saturating_sub(MARKER_WIDTH + 1)
+                )
+            )?;
+        }
 
-                    dep.toggle_feature(self.feature_selector.get_selected()?.name())?;
+        self.display_scrollbar(feature_range)?;
 
-                    save_dependency(
-                        &mut self.document,
-                        self.package_selector.get_selected()?.name(),
-                        dep_name,
-                    )?;
-                }
-            }
+        Ok(())
+    }
 
-            //search
-            (Key::Char(char), _) => {
-                if char == ' ' {
-                    return Ok(RunningState::Running);
-                }
+    /// the markers of the left & right dependency in front of each feature - blank if the dependency does not have it
+    fn display_compare(&mut self) -> Result<()> {
+        let [left, right] = self.compared.clone().context("nothing to compare")?;
 
-                self.search_text += char.to_string().as_str();
+        let header = format!(
+            "{} {} {}",
+            self.compare_label(&left)?,
+            style("with").color256(8),
+            self.compare_label(&right)?
+        );
 
-                self.update_selected_data()?;
+        self.display_header(&header)?;
 
-                match self.state {
-                    DisplayState::Dep => self.dep_selector.shift(0),
-                    DisplayState::Feature => self.feature_selector.shift(0),
-                    DisplayState::Package => self.package_selector.shift(0),
-                }
+        let deps = [
+            self.document.get_package(&left.0)?.get_dep(&left.1)?,
+            self.document.get_package(&right.0)?.get_dep(&right.1)?,
+        ];
+
+        // computed once instead of per row - a dependency can have hundreds of features
+        let implied = deps.map(|dep| dep.get_implied_features());
+
+        let feature_range = self.get_max_range()?;
+        let layout = Layout::new(self.width());
+
+        for (line_index, index) in (1..).zip(feature_range.clone()) {
+            let feature = &self.compare_selector.data[index];
+
+            if index == self.compare_selector.selected_index {
+                self.term.move_cursor_to(0, line_index)?;
+                write!(self.term, ">")?;
             }
-            (Key::Backspace, _) => {
-                let _ = self.search_text.pop();
 
-                self.update_selected_data()?;
+            let mut row = String::new();
+
+            for (dep, implied) in deps.iter().zip(&implied) {
+                let marker = match dep.get_feature(feature.name()) {
+                    Some(data) => {
+                        let marker = pad_str(
+                            &get_marker(data, implied.contains(feature.name())),
+                            MARKER_WIDTH,
+                            Alignment::Left,
+                            None,
+                        )
+                        .to_string();
+
+                        if data.is_default {
+                            style(marker).green().to_string()
+                        } else {
+                            marker
+                        }
+                    }
+                    None => " ".repeat(MARKER_WIDTH),
+                };
+
+                row.push_str(&format!("{} ", marker));
             }
 
-            //back
-            (Key::Escape, _) | (Key::ArrowLeft, _) => {
-                return self.move_back();
+            row.push_str(feature.display_name());
+
+            match deps.map(|dep| dep.get_feature(feature.name()).is_some()) {
+                [true, false] => row.push_str(&style(" (only left)").color256(8).to_string()),
+                [false, true] => row.push_str(&style(" (only right)").color256(8).to_string()),
+                _ => {}
             }
 
-            _ => {}
+            self.term.move_cursor_to(layout.name, line_index)?;
+            write!(self.term, "{}", fit_to_width(&row, layout.name_width))?;
         }
 
-        Ok(RunningState::Running)
+        self.display_scrollbar(feature_range)?;
+
+        Ok(())
     }
 
-    fn get_max_range(&self) -> Result<Range<usize>> {
-        let current_selected = match self.state {
-            DisplayState::Dep => self.dep_selector.selected_index,
-            DisplayState::Feature => self.feature_selector.selected_index,
-            DisplayState::Package => self.package_selector.selected_index,
-        } as isize;
+    /// the dependency with its version - followed by the package in a workspace
+    fn compare_label(&self, (package_name, dep_name): &(String, String)) -> Result<String> {
+        let dep = self.document.get_package(package_name)?.get_dep(dep_name)?;
 
-        let max_range = match self.state {
-            DisplayState::Dep => self.dep_selector.data.len(),
-            DisplayState::Feature => self.feature_selector.data.len(),
-            DisplayState::Package => self.package_selector.data.len(),
-        };
+        let mut label = format!("{} {}", dep.get_name(), dep.get_version());
 
-        let mut offset = 0;
+        if self.document.is_workspace() {
+            label.push_str(
+                &style(format!(" ({})", package_name))
+                    .color256(8)
+                    .to_string(),
+            );
+        }
 
-        if let DisplayState::Feature = self.state {
-            if self.feature_selector.has_data() {
-                let dep = self
-                    .document
-                    .get_package(self.package_selector.get_selected()?.name())?
-                    .get_dep(self.dep_selector.get_selected()?.name())?;
+        Ok(label)
+    }
 
-                let feature = self.feature_selector.get_selected()?;
-                let data = dep
-                    .get_feature(feature.name())
-                    .context(format!("coundt find {}", feature.name()))?;
+    /// draws a scrollbar on the right edge if not all items fit on the screen
+    fn display_scrollbar(&mut self, range: Range<usize>) -> Result<()> {
+        let total = self.selector().data.len();
+        let height = range.len();
 
-                if !data.sub_features.is_empty() {
-                    offset = 1;
-                }
-            }
+        if height == 0 || total <= height {
+            return Ok(());
         }
 
-        let height = self.term.size().0 as usize;
+        let thumb_size = (height * height / total).max(1);
+        let thumb_start =
+            (range.start * (height - thumb_size) / (total - height)).min(height - thumb_size);
 
-        let start = (current_selected - height as isize / 2 + 1)
-            .min(max_range as isize - height as isize + 1 + offset as isize)
-            .max(0) as usize;
+        let column = self.term.size().1 as usize - 1;
 
-        Ok(start..max_range.min(start + height - 1 - offset))
-    }
+        for row in 0..height {
+            self.term.move_cursor_to(column, row + 1)?;
 
-    fn update_selected_data(&mut self) -> Result<()> {
-        match self.state {
-            DisplayState::Package => {
-                self.package_selector.data =
-                    FilterView::data_from_document(&self.document, &self.search_text)?;
+            if (thumb_start..thumb_start + thumb_size).contains(&row) {
+                write!(self.term, "█")?;
+            } else {
+                write!(self.term, "{}", style("│").color256(8))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// the legend sits right above the notice - the rows above it are shortened by `get_max_range`
+    fn display_legend(&mut self) -> Result<()> {
+        let lines = self.legend_lines();
+
+        let mut bottom = self.term.size().0 as usize;
+
+        if self.notice.is_some() || self.error.is_some() || self.goto.is_some() {
+            bottom = bottom.saturating_sub(1);
+        }
+
+        for (row, line) in (bottom.saturating_sub(lines.len())..).zip(lines) {
+            self.term.move_cursor_to(0, row)?;
+            write!(self.term, "{}", line)?;
+        }
+
+        Ok(())
+    }
+
+    /// at most half of the screen - the rows stay usable on small terminals
+    fn legend_lines(&self) -> Vec<String> {
+        if !self.show_legend {
+            return vec![];
+        }
+
+        let mut lines = wrap_legend(&get_legend(&self.state), self.width());
+        lines.truncate(self.term.size().0 as usize / 2);
+
+        lines
+    }
+
+    fn display_notice(&mut self) -> Result<()> {
+        if let Some(goto) = &self.goto {
+            self.term
+                .move_cursor_to(0, self.term.size().0 as usize - 1)?;
+            write!(
+                self.term,
+                ":{} {}",
+                goto,
+                style(format!("- go to 1-{}", self.selector().data.len())).color256(8)
+            )?;
+        } else if let Some(error) = &self.error {
+            self.term
+                .move_cursor_to(0, self.term.size().0 as usize - 1)?;
+            write!(
+                self.term,
+                "{}",
+                style(fit_to_width(&format!("error: {}", error), self.width())).red()
+            )?;
+        } else if let Some(notice) = &self.notice {
+            self.term
+                .move_cursor_to(0, self.term.size().0 as usize - 1)?;
+            write!(
+                self.term,
+                "{}",
+                style(fit_to_width(notice, self.width())).yellow()
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// the header is cut to the width - it would wrap into the first row otherwise
+    ///
+    /// the search is cut last - it is the part which changes while typing
+    fn display_header(&mut self, header: &str) -> Result<()> {
+        let search = self.search_header();
+        let width = self.width();
+
+        let header = fit_to_width(header, width.saturating_sub(measure_text_width(&search)));
+
+        write!(
+            self.term,
+            "{}",
+            fit_to_width(&format!("{}{}", header, search), width)
+        )?;
+
+        Ok(())
+    }
+
+    /// the last column is kept free for the scrollbar
+    fn width(&self) -> usize {
+        (self.term.size().1 as usize).saturating_sub(1)
+    }
+
+    fn search_header(&self) -> String {
+        let mut header = String::new();
+
+        if !self.search_text.is_empty() {
+            let matches = self
+                .selector()
+                .data
+                .iter()
+                .filter(|item| !item.is_group())
+                .count();
+
+            header.push_str(&format!(
+                " - {} {}",
+                self.search_text,
+                style(format!(
+                    "({} {})",
+                    matches,
+                    if matches == 1 { "match" } else { "matches" }
+                ))
+                .color256(8)
+            ));
+
+            if matches == 0 && matches!(self.state, DisplayState::Feature) {
+                header.push_str(&format!(
+                    " {}",
+                    style("- press Ctrl+N to add it as a custom feature").color256(8)
+                ));
+            }
+        }
+
+        header
+    }
+
+    fn input_event(&mut self) -> Result<RunningState> {
+        let key = self.term.read_key()?;
+
+        self.notice = None;
+        self.error = None;
+
+        let pending_transitive = self.pending_transitive.take();
+
+        if self.goto.is_some() {
+            return self.goto_input(key);
+        }
+
+        match (key, &self.state) {
+            //movement
+            //up
+            (Key::ArrowUp, DisplayState::Package) => {
+                self.package_selector.shift(-1);
+            }
+            (Key::ArrowUp, DisplayState::Dep) => {
+                self.dep_selector.shift(-1);
+            }
+            (Key::ArrowUp, DisplayState::Feature) => {
+                self.feature_selector.shift(-1);
+            }
+            (Key::ArrowUp, DisplayState::AllFeatures) => {
+                self.all_features_selector.shift(-1);
+            }
+            (Key::ArrowUp, DisplayState::Compare) => {
+                self.compare_selector.shift(-1);
+            }
+            //down
+            (Key::ArrowDown, DisplayState::Package) => {
+                self.package_selector.shift(1);
+            }
+            (Key::ArrowDown, DisplayState::Dep) => {
+                self.dep_selector.shift(1);
+            }
+            (Key::ArrowDown, DisplayState::Feature) => {
+                self.feature_selector.shift(1);
+            }
+            (Key::ArrowDown, DisplayState::AllFeatures) => {
+                self.all_features_selector.shift(1);
+            }
+            (Key::ArrowDown, DisplayState::Compare) => {
+                self.compare_selector.shift(1);
+            }
+
+            //selection
+            (Key::Enter, DisplayState::Package)
+            | (Key::ArrowRight, DisplayState::Package)
+            | (Key::Char(' '), DisplayState::Package)
+                if self.package_selector.has_data() =>
+            {
+                let name = self.package_selector.get_selected()?.name();
+
+                if !self
+                    .document
+                    .get_package(name)
+                    .context(format!("package not found - {}", name))?
+                    .dependencies
+                    .is_empty()
+                {
+                    self.search_text = "".to_string();
+
+                    self.select_selected_package()?;
+
+                    //needed to wrap
+                    self.dep_selector.shift(0);
+                }
+            }
+            (Key::Enter, DisplayState::Dep)
+            | (Key::ArrowRight, DisplayState::Dep)
+            | (Key::Char(' '), DisplayState::Dep)
+                if self.dep_selector.has_data()
+                    && self.dep_selector.get_selected()?.is_transitive() =>
+            {
+                if self.deny_write() || self.deny_scratch("add a dependency") {
+                    return Ok(RunningState::Running);
+                }
+
+                let package_name = self.package_selector.get_selected()?.name().to_string();
+                let dep_name = self.dep_selector.get_selected()?.name().to_string();
+
+                if pending_transitive.as_ref() == Some(&dep_name) {
+                    self.add_transitive(&package_name, &dep_name)?;
+                } else {
+                    self.notice = Some(format!(
+                        "{} is a transitive dependency - select it again to add it to [dependencies] of {}",
+                        dep_name, package_name
+                    ));
+                    self.pending_transitive = Some(dep_name);
+                }
+            }
+            (Key::Enter, DisplayState::Dep)
+            | (Key::ArrowRight, DisplayState::Dep)
+            | (Key::Char(' '), DisplayState::Dep)
+                if self.dep_selector.has_data() =>
+            {
+                let dep = self
+                    .document
+                    .get_package(self.package_selector.get_selected()?.name())?
+                    .get_dep(self.dep_selector.get_selected()?.name())?;
+
+                if dep.has_features() {
+                    self.search_text = "".to_string();
+
+                    self.select_selected_dep()?;
+
+                    //needed to wrap
+                    self.feature_selector.shift(0);
+                } else {
+                    self.notice = Some(format!("{} has no features", dep.get_name()));
+                }
+            }
+            (Key::Enter, DisplayState::Feature)
+            | (Key::ArrowRight, DisplayState::Feature)
+            | (Key::Char(' '), DisplayState::Feature)
+                if self.feature_selector.has_data()
+                    && self.feature_selector.get_selected()?.is_defaults() =>
+            {
+                self.feature_options.collapse_defaults = false;
+
+                self.update_selected_data()?;
+            }
+            (Key::Enter, DisplayState::Feature)
+            | (Key::ArrowRight, DisplayState::Feature)
+            | (Key::Char(' '), DisplayState::Feature)
+                if self.feature_selector.has_data()
+                    && self.feature_selector.get_selected()?.is_group() =>
+            {
+                let group = self.feature_selector.get_selected()?.name().to_string();
+
+                self.feature_options.toggle_group(&group);
+
+                self.update_selected_data()?;
+            }
+            (Key::Char(' '), DisplayState::Feature) if self.feature_selector.has_data() => {
+                if self.deny_write() || self.deny_workspace_feature()? {
+                    return Ok(RunningState::Running);
+                }
+
+                let feature_name = self.feature_selector.get_selected()?.name().to_string();
+
+                if let Some(index) = self
+                    .marked
+                    .iter()
+                    .position(|(name, _)| *name == feature_name)
+                {
+                    self.marked.remove(index);
+                } else {
+                    let is_enabled = self
+                        .document
+                        .get_package(self.package_selector.get_selected()?.name())?
+                        .get_dep(self.dep_selector.get_selected()?.name())?
+                        .get_feature(&feature_name)
+                        .map_or(false, |data| data.is_enabled());
+
+                    self.marked.push((feature_name, !is_enabled));
+                }
+
+                if !self.marked.is_empty() {
+                    self.notice = Some(format!(
+                        "{} marked - enter applies the changes, escape discards them",
+                        self.marked.len()
+                    ));
+                }
+            }
+            (Key::Enter, DisplayState::Feature) | (Key::ArrowRight, DisplayState::Feature)
+                if self.feature_selector.has_data() && !self.marked.is_empty() =>
+            {
+                let marked = std::mem::take(&mut self.marked);
+
+                self.change_features(&marked)?;
+            }
+            (Key::Enter, DisplayState::Feature) | (Key::ArrowRight, DisplayState::Feature)
+                if self.feature_selector.has_data() =>
+            {
+                if self.deny_write() || self.deny_workspace_feature()? {
+                    return Ok(RunningState::Running);
+                }
+
+                let feature_name = self.feature_selector.get_selected()?.name().to_string();
+
+                let is_enabled = self
+                    .document
+                    .get_package(self.package_selector.get_selected()?.name())?
+                    .get_dep(self.dep_selector.get_selected()?.name())?
+                    .get_feature(&feature_name)
+                    .map_or(false, |data| data.is_enabled());
+
+                self.change_features(&[(feature_name, !is_enabled)])?;
+            }
+
+            (Key::Enter, DisplayState::AllFeatures)
+            | (Key::ArrowRight, DisplayState::AllFeatures)
+                if self.all_features_selector.has_data() =>
+            {
+                self.open_selected_location()?;
+            }
+
+            //search across all dependencies
+            (Key::Char(CTRL_K), DisplayState::AllFeatures) => {
+                return self.move_back();
+            }
+            (Key::Char(CTRL_K), _) => {
+                self.open_view(DisplayState::AllFeatures)?;
+            }
+
+            //compare
+            (Key::Char(CTRL_P), DisplayState::Compare) => {
+                return self.move_back();
+            }
+            (Key::Char(CTRL_P), DisplayState::Dep) | (Key::Char(CTRL_P), DisplayState::Feature)
+                if self.has_direct_dep_selected()? =>
+            {
+                let selected = self.get_position_key()?;
+
+                match self.compare_pick.take() {
+                    Some(picked) if picked == selected => {
+                        self.notice = Some(format!("dropped the pick of {}", selected.1));
+                    }
+                    Some(picked) => {
+                        self.compared = Some([picked, selected]);
+                        self.open_view(DisplayState::Compare)?;
+                    }
+                    None => {
+                        self.notice = Some(format!(
+                            "picked {} - press Ctrl+P on another dependency to compare them",
+                            selected.1
+                        ));
+                        self.compare_pick = Some(selected);
+                    }
+                }
+            }
+
+            //docs
+            (Key::Char(CTRL_O), DisplayState::Dep) | (Key::Char(CTRL_O), DisplayState::Feature)
+                if self.has_direct_dep_selected()? =>
+            {
+                let url = self
+                    .document
+                    .get_package(self.package_selector.get_selected()?.name())?
+                    .get_dep(self.dep_selector.get_selected()?.name())?
+                    .get_docs_url();
+
+                if open::that_detached(&url).is_err() {
+                    self.notice = Some(format!("could not open {}", url));
+                }
+            }
+
+            //copy
+            (Key::Char(CTRL_Y), DisplayState::Dep) | (Key::Char(CTRL_Y), DisplayState::Feature)
+                if self.has_direct_dep_selected()? =>
+            {
+                let line = render_dependency(
+                    &self.document,
+                    self.package_selector.get_selected()?.name(),
+                    self.dep_selector.get_selected()?.name(),
+                )?;
+
+                self.notice = Some(match copy_to_clipboard(&line) {
+                    Ok(_) => format!("copied: {}", line),
+                    Err(_) => format!("could not access the clipboard - {}", line),
+                });
+            }
+
+            //apply to all packages
+            (Key::Char(CTRL_W), DisplayState::Dep) | (Key::Char(CTRL_W), DisplayState::Feature)
+                if self.has_direct_dep_selected()? && self.document.is_workspace() =>
+            {
+                if self.deny_write() {
+                    return Ok(RunningState::Running);
+                }
+
+                let package_name = self.package_selector.get_selected()?.name();
+                let dep_name = self.dep_selector.get_selected()?.name();
+
+                let changed = self
+                    .document
+                    .apply_features_to_all_packages(package_name, dep_name)?;
+
+                for name in &changed {
+                    if let Err(err) = save_dependency(&mut self.document, name, dep_name) {
+                        self.notice = Some(format!("could not save {} - {:#}", name, err));
+                        return Ok(RunningState::Running);
+                    }
+                }
+
+                self.notice = Some(if changed.is_empty() {
+                    format!("no other package needed changes for {}", dep_name)
+                } else {
+                    format!(
+                        "applied the features of {} to {}",
+                        dep_name,
+                        changed.join(", ")
+                    )
+                });
+
+                self.update_selected_data()?;
+            }
+
+            //optional
+            (Key::Char(CTRL_T), DisplayState::Dep) | (Key::Char(CTRL_T), DisplayState::Feature)
+                if self.has_direct_dep_selected()? =>
+            {
+                if self.deny_write() {
+                    return Ok(RunningState::Running);
+                }
+
+                let package_name = self.package_selector.get_selected()?.name();
+                let dep_name = self.dep_selector.get_selected()?.name();
+
+                let package = self.document.get_package_mut(package_name)?;
+                let manifest_path = package.manifest_path.clone();
+                let dep = package.get_dep_mut(dep_name)?;
+
+                if !dep.can_be_optional() {
+                    self.notice = Some(format!("{} can not be optional", dep.get_name()));
+                    return Ok(RunningState::Running);
+                }
+
+                if !dep.optional && has_feature_named_like_dependency(&manifest_path, &dep.name)? {
+                    self.notice = Some(format!(
+                        "{} can not be optional - it collides with the feature of the same name",
+                        dep.get_name()
+                    ));
+                    return Ok(RunningState::Running);
+                }
+
+                dep.optional = !dep.optional;
+
+                if !dep.optional {
+                    let features = get_features_activating_dependency(manifest_path, &dep.name)?;
+
+                    if !features.is_empty() {
+                        self.notice = Some(format!(
+                            "{} is still activated via dep:{} by {} - cargo requires it to be optional",
+                            dep.get_name(),
+                            dep.name,
+                            features.join(", ")
+                        ));
+                    }
+                }
+
+                if let Err(err) = save_dependency(&mut self.document, package_name, dep_name) {
+                    self.notice = Some(format!("could not save {} - {:#}", dep_name, err));
+                }
+
+                self.update_selected_data()?;
+            }
+
+            //reload
+            (Key::Char(CTRL_R), _) => {
+                if self.deny_scratch("reload") {
+                    return Ok(RunningState::Running);
+                }
+
+                if let Err(err) = self.reload() {
+                    self.notice = Some(format!("could not reload - {}", err));
+                }
+            }
+
+            //custom feature
+            (Key::Char(CTRL_N), DisplayState::Feature) => {
+                if self.deny_write() {
+                    return Ok(RunningState::Running);
+                }
+
+                if self.search_text.is_empty() {
+                    self.notice =
+                        Some("type the name of the feature first - then press Ctrl+N".to_string());
+                    return Ok(RunningState::Running);
+                }
+
+                let feature_name = std::mem::take(&mut self.search_text);
+                let dep_name = self.dep_selector.get_selected()?.name();
+
+                let dep = self
+                    .document
+                    .get_package_mut(self.package_selector.get_selected()?.name())?
+                    .get_dep_mut(dep_name)?;
+
+                if dep.add_custom_feature(&feature_name)? {
+                    self.notice = Some(format!(
+                        "added {} - it is unknown to {} {} and may fail to compile",
+                        feature_name,
+                        dep.name,
+                        dep.get_version()
+                    ));
+                }
+
+                if let Err(err) = save_dependency(
+                    &mut self.document,
+                    self.package_selector.get_selected()?.name(),
+                    dep_name,
+                ) {
+                    self.notice = Some(format!("could not save {} - {:#}", dep_name, err));
+                }
+
+                self.update_selected_data()?;
+                self.feature_selector.select(&feature_name);
+            }
+
+            //switch package
+            (Key::Tab, DisplayState::Dep) | (Key::Tab, DisplayState::Feature) => {
+                self.cycle_package(1)?;
+            }
+            (Key::BackTab, DisplayState::Dep) | (Key::BackTab, DisplayState::Feature) => {
+                self.cycle_package(-1)?;
+            }
+
+            //options
+            (Key::Char(CTRL_F), DisplayState::Dep) => {
+                self.dep_kind_filter = self.dep_kind_filter.next();
+
+                self.update_selected_data()?;
+
+                if !self.dep_selector.has_data() {
+                    self.notice = Some(format!("no {} dependencies", self.dep_kind_filter.label()));
+                }
+            }
+            (Key::Char(CTRL_L), DisplayState::Dep) => {
+                if self.transitive.take().is_none() {
+                    self.load_transitive()?;
+                }
+
+                self.update_selected_data()?;
+
+                let package_name = self.package_selector.get_selected()?.name();
+                let count = self
+                    .transitive
+                    .as_ref()
+                    .and_then(|transitive| transitive.get(package_name))
+                    .map(|dependencies| dependencies.len());
+
+                self.notice = Some(match count {
+                    None => "hid the transitive dependencies".to_string(),
+                    Some(0) => format!("{} has no transitive dependencies", package_name),
+                    Some(_) if !self.dep_kind_filter.includes_transitive() => format!(
+                        "transitive dependencies are not shown with the {} filter",
+                        self.dep_kind_filter.label()
+                    ),
+                    Some(1) => "showing 1 transitive dependency".to_string(),
+                    Some(count) => format!("showing {} transitive dependencies", count),
+                });
+            }
+            (Key::Char(CTRL_F), DisplayState::Feature) => {
+                self.feature_options.state_filter = self.feature_options.state_filter.next();
+
+                self.update_selected_data()?;
+            }
+            (Key::Char(CTRL_B), DisplayState::Feature) => {
+                self.feature_options.sort = self.feature_options.sort.next();
+
+                self.update_selected_data()?;
+
+                self.notice = Some(format!("sorted {}", self.feature_options.sort.label()));
+            }
+            (Key::Char(CTRL_U), DisplayState::Dep) | (Key::Char(CTRL_U), DisplayState::Feature) => {
+                if self.deny_write() {
+                    return Ok(RunningState::Running);
+                }
+
+                let package_name = self.package_selector.get_selected()?.name().to_string();
+
+                self.notice = Some(
+                    match cleanup_package(&mut self.document, &package_name, false) {
+                        Ok(cleaned) if cleaned.is_empty() => {
+                            "nothing to clean up - the feature lists are minimal".to_string()
+                        }
+                        Ok(cleaned) if cleaned.len() == 1 => {
+                            format!("cleaned up {}", cleaned[0])
+                        }
+                        Ok(cleaned) => format!("cleaned up {} dependencies", cleaned.len()),
+                        Err(err) => format!("could not clean up {} - {:#}", package_name, err),
+                    },
+                );
+            }
+            (Key::Char(CTRL_X), DisplayState::Feature) => {
+                self.feature_options.explicit_only = !self.feature_options.explicit_only;
+
+                self.notice = Some(if self.feature_options.explicit_only {
+                    "explicit mode - toggling a feature no longer changes its sub features or the features requiring it".to_string()
+                } else {
+                    "cascade mode - toggling a feature also toggles its sub features and the features requiring it".to_string()
+                });
+            }
+            (Key::Char(CTRL_D), DisplayState::Feature) => {
+                let selected = self.feature_selector.get_selected_name();
+
+                self.feature_options.collapse_defaults = !self.feature_options.collapse_defaults;
+
+                self.update_selected_data()?;
+
+                // a collapsed default feature is replaced by the default row
+                if self.feature_selector.get_selected_name() != selected {
+                    self.feature_selector.select("default");
+                }
+            }
+            (Key::Char(CTRL_G), DisplayState::Feature) => {
+                self.feature_options.group_by_prefix = !self.feature_options.group_by_prefix;
+
+                self.update_selected_data()?;
+            }
+
+            //quit
+            (Key::CtrlC, _) | (Key::Char(CTRL_Q), _) => {
+                return Ok(RunningState::Finished);
+            }
+
+            //legend - names can not contain `?` so it never is part of a search
+            (Key::Char('?'), _) => {
+                self.show_legend = !self.show_legend;
+            }
+
+            //go to - names can not contain `:` so it never is part of a search
+            (Key::Char(':'), _) => {
+                self.goto = Some("".to_string());
+            }
+
+            //search
+            (Key::Char(char), _) => {
+                if char == ' ' || char.is_control() {
+                    return Ok(RunningState::Running);
+                }
+
+                self.search_text += char.to_string().as_str();
+
+                self.update_selected_data()?;
+            }
+            (Key::Backspace, _) => {
+                let _ = self.search_text.pop();
+
+                self.update_selected_data()?;
+            }
+
+            //back
+            (Key::Escape, DisplayState::Feature) | (Key::ArrowLeft, DisplayState::Feature)
+                if !self.marked.is_empty() =>
+            {
+                self.notice = Some(format!("discarded {} marked", self.marked.len()));
+                self.marked.clear();
+            }
+            (Key::Escape, _) | (Key::ArrowLeft, _) => {
+                return self.move_back();
+            }
+
+            _ => {}
+        }
+
+        Ok(RunningState::Running)
+    }
+
+    /// input while the go to prompt is open - digits, backspace, enter to jump & anything else to cancel
+    fn goto_input(&mut self, key: Key) -> Result<RunningState> {
+        match key {
+            Key::Char(char) if char.is_ascii_digit() => {
+                if let Some(goto) = &mut self.goto {
+                    goto.push(char);
+                }
+            }
+            Key::Backspace => {
+                if self.goto.as_mut().and_then(|goto| goto.pop()).is_none() {
+                    self.goto = None;
+                }
+            }
+            Key::Enter => {
+                let row = self.goto.take().unwrap_or_default();
+                let len = self.selector().data.len();
+
+                let selected = row
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|row| row.checked_sub(1))
+                    .map_or(false, |index| self.selector_mut().select_index(index));
+
+                if !selected && !row.is_empty() {
+                    self.notice = Some(format!("there is no row {} - only 1-{}", row, len));
+                }
+            }
+            _ => {
+                self.goto = None;
+            }
+        }
+
+        Ok(RunningState::Running)
+    }
+
+    fn get_max_range(&self) -> Result<Range<usize>> {
+        let current_selected = match self.state {
+            DisplayState::Dep => self.dep_selector.selected_index,
+            DisplayState::Feature => self.feature_selector.selected_index,
+            DisplayState::Package => self.package_selector.selected_index,
+            DisplayState::AllFeatures => self.all_features_selector.selected_index,
+            DisplayState::Compare => self.compare_selector.selected_index,
+        };
+
+        let max_range = match self.state {
+            DisplayState::Dep => self.dep_selector.data.len(),
+            DisplayState::Feature => self.feature_selector.data.len(),
+            DisplayState::Package => self.package_selector.data.len(),
+            DisplayState::AllFeatures => self.all_features_selector.data.len(),
+            DisplayState::Compare => self.compare_selector.data.len(),
+        };
+
+        let mut offset = 0;
+
+        if let DisplayState::Feature = self.state {
+            if self.feature_selector.has_data() && !self.feature_selector.get_selected()?.is_group()
+            {
+                let dep = self
+                    .document
+                    .get_package(self.package_selector.get_selected()?.name())?
+                    .get_dep(self.dep_selector.get_selected()?.name())?;
+
+                let feature = self.feature_selector.get_selected()?;
+                let data = dep
+                    .get_feature(feature.name())
+                    .context(format!("coundt find {}", feature.name()))?;
+
+                if !data.sub_features.is_empty() {
+                    offset += 1;
+                }
+
+                if data.note.is_some() {
+                    offset += 1;
+                }
+            }
+        }
+
+        let mut height = self.term.size().0 as usize;
+
+        if self.notice.is_some() || self.error.is_some() || self.goto.is_some() {
+            height = height.saturating_sub(1);
+        }
+
+        height = height.saturating_sub(self.legend_lines().len());
+
+        Ok(get_window(current_selected, max_range, height, offset))
+    }
+
+    fn update_selected_data(&mut self) -> Result<()> {
+        match self.state {
+            DisplayState::Package => {
+                self.package_selector
+                    .set_data(FilterView::data_from_document(
+                        &self.document,
+                        &self.search_text,
+                    )?);
             }
             DisplayState::Dep => {
-                let package = self
-                    .document
-                    .get_package(self.package_selector.get_selected()?.name())?;
+                let data = self.dep_data(
+                    self.package_selector.get_selected()?.name(),
+                    &self.search_text,
+                )?;
 
-                self.dep_selector.data = FilterView::data_from_package(package, &self.search_text)?;
+                self.dep_selector.set_data(data);
             }
             DisplayState::Feature => {
                 let dep = self
@@ -460,11 +1596,389 @@ impl Display {
                     .get_package(self.package_selector.get_selected()?.name())?
                     .get_dep(self.dep_selector.get_selected()?.name())?;
 
-                self.feature_selector.data =
-                    FilterView::data_from_dependency(dep, &self.search_text);
+                self.feature_selector
+                    .set_data(FilterView::data_from_dependency(
+                        dep,
+                        &self.search_text,
+                        &self.feature_options,
+                    ));
+            }
+            DisplayState::AllFeatures => {
+                self.all_features_selector
+                    .set_data(FilterView::data_from_all_features(
+                        self.document.get_packages(),
+                        &self.search_text,
+                        self.document.is_workspace(),
+                    ));
+            }
+            DisplayState::Compare => {
+                let [left, right] = self.compared.as_ref().context("nothing to compare")?;
+
+                let data = FilterView::data_from_comparison(
+                    self.document.get_package(&left.0)?.get_dep(&left.1)?,
+                    self.document.get_package(&right.0)?.get_dep(&right.1)?,
+                    &self.search_text,
+                );
+
+                self.compare_selector.set_data(data);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn selector(&self) -> &FilterView {
+        match self.state {
+            DisplayState::Package => &self.package_selector,
+            DisplayState::Dep => &self.dep_selector,
+            DisplayState::Feature => &self.feature_selector,
+            DisplayState::AllFeatures => &self.all_features_selector,
+            DisplayState::Compare => &self.compare_selector,
+        }
+    }
+
+    fn selector_mut(&mut self) -> &mut FilterView {
+        match self.state {
+            DisplayState::Package => &mut self.package_selector,
+            DisplayState::Dep => &mut self.dep_selector,
+            DisplayState::Feature => &mut self.feature_selector,
+            DisplayState::AllFeatures => &mut self.all_features_selector,
+            DisplayState::Compare => &mut self.compare_selector,
+        }
+    }
+
+    /// the rows of the dependency selector - followed by the transitive dependencies while they are shown
+    fn dep_data(&self, package_name: &str, filter: &str) -> Result<Vec<FilterViewItem>> {
+        let mut data = FilterView::data_from_package(
+            self.document.get_package(package_name)?,
+            filter,
+            self.dep_kind_filter,
+        )?;
+
+        if let Some(transitive) = self
+            .transitive
+            .as_ref()
+            .and_then(|transitive| transitive.get(package_name))
+        {
+            if self.dep_kind_filter.includes_transitive() {
+                data.extend(FilterView::data_from_transitive(transitive, filter));
+            }
+        }
+
+        Ok(data)
+    }
+
+    /// never change a manifest - e.g. to review the features of someone else's project
+    pub fn set_read_only(&mut self, read_only: bool) {
+        self.read_only = read_only;
+    }
+
+    /// the manifests changed in scratch mode by path - see `Document::take_scratch`
+    pub fn take_scratch(&mut self) -> BTreeMap<String, String> {
+        self.document.take_scratch()
+    }
+
+    /// returns true in scratch mode & shows a notice - reloading or adding a dependency would lose the changes in memory
+    fn deny_scratch(&mut self, action: &str) -> bool {
+        if self.document.is_scratch() {
+            self.notice = Some(format!(
+                "can not {} in scratch mode - quit to apply or discard the changes first",
+                action
+            ));
+        }
+
+        self.document.is_scratch()
+    }
+
+    /// returns true in read-only mode & shows a notice instead of the change
+    fn deny_write(&mut self) -> bool {
+        if self.read_only {
+            self.notice =
+                Some("read-only - started with --read-only, nothing is changed".to_string());
+        }
+
+        self.read_only
+    }
+
+    /// default-features & features of `workspace = true` can only be changed in `[workspace.dependencies]` - shows a notice if the selected feature is one of them
+    fn deny_workspace_feature(&mut self) -> Result<bool> {
+        let feature_name = self.feature_selector.get_selected()?.name();
+
+        let is_workspace = self
+            .document
+            .get_package(self.package_selector.get_selected()?.name())?
+            .get_dep(self.dep_selector.get_selected()?.name())?
+            .get_feature(feature_name)
+            .map_or(false, |data| data.enabled_state == EnabledState::Workspace);
+
+        if is_workspace {
+            self.notice = Some(format!(
+                "{} is enabled by the workspace - change it in [workspace.dependencies] of the root Cargo.toml",
+                feature_name
+            ));
+        }
+
+        Ok(is_workspace)
+    }
+
+    /// enables or disables the features of the selected dependency & saves it once
+    ///
+    /// reports optional dependencies which are now compiled & resolves conflicts with the previously enabled features
+    fn change_features(&mut self, changes: &[(String, bool)]) -> Result<()> {
+        let dep_name = self.dep_selector.get_selected()?.name();
+
+        let dep = self
+            .document
+            .get_package_mut(self.package_selector.get_selected()?.name())?
+            .get_dep_mut(dep_name)?;
+
+        let enabled_dependencies = dep.get_enabled_optional_dependencies();
+        let enabled_features = dep
+            .features
+            .iter()
+            .filter(|(_, data)| data.is_enabled())
+            .map(|(name, _)| name.to_string())
+            .collect::<HashSet<_>>();
+
+        apply_changes(dep, changes, self.feature_options.explicit_only)?;
+
+        for (feature_name, _) in changes {
+            debug!(
+                "toggled {} of {} - enabled: {}",
+                feature_name,
+                dep_name,
+                dep.get_feature(feature_name)
+                    .map_or(false, |data| data.is_enabled())
+            );
+        }
+
+        let activated_dependencies = dep
+            .get_enabled_optional_dependencies()
+            .into_iter()
+            .filter(|name| !enabled_dependencies.contains(name))
+            .collect_vec();
+
+        if !activated_dependencies.is_empty() {
+            self.notice = Some(format!(
+                "{} {} enables the optional dependencies {} - they will now be compiled as well",
+                Emoji("📦", "dep:"),
+                changes.iter().map(|(name, _)| name).join(", "),
+                activated_dependencies.join(", ")
+            ));
+        }
+
+        let conflicts = resolve_conflicts(dep, &enabled_features)?;
+
+        if !conflicts.is_empty() {
+            self.notice = Some(conflicts.join(" - "));
+        }
+
+        if let Err(err) = save_dependency(
+            &mut self.document,
+            self.package_selector.get_selected()?.name(),
+            dep_name,
+        ) {
+            warn!("could not save {}: {:?}", dep_name, err);
+            self.notice = Some(format!("could not save {} - {:#}", dep_name, err));
+        }
+
+        Ok(())
+    }
+
+    fn has_direct_dep_selected(&self) -> Result<bool> {
+        Ok(self.dep_selector.has_data() && !self.dep_selector.get_selected()?.is_transitive())
+    }
+
+    fn load_transitive(&mut self) -> Result<()> {
+        let metadata = get_resolved_metadata(self.document.is_frozen())?;
+
+        self.transitive = Some(
+            self.document
+                .get_packages()
+                .iter()
+                .map(|package| {
+                    (
+                        package.name.to_string(),
+                        get_transitive_dependencies(&metadata, &package.name),
+                    )
+                })
+                .collect(),
+        );
+
+        Ok(())
+    }
+
+    /// writes the transitive dependency into the manifest of the package & opens its features
+    fn add_transitive(&mut self, package_name: &str, dep_name: &str) -> Result<()> {
+        let version = self
+            .transitive
+            .as_ref()
+            .and_then(|transitive| transitive.get(package_name))
+            .and_then(|dependencies| dependencies.iter().find(|dep| dep.name == dep_name))
+            .map(|dep| dep.version.to_string())
+            .context(format!(
+                "could not find the transitive dependency {}",
+                dep_name
+            ))?;
+
+        add_dependency(
+            &self.document.get_package(package_name)?.manifest_path,
+            dep_name,
+            &version,
+        )?;
+
+        self.search_text = "".to_string();
+        self.reload()?;
+        self.set_selected_dep(Some(package_name), dep_name.to_string())?;
+
+        self.notice = Some(format!(
+            "added {} = \"{}\" to [dependencies] of {}",
+            dep_name, version, package_name
+        ));
+
+        Ok(())
+    }
+
+    fn reload(&mut self) -> Result<()> {
+        let package_name = self.package_selector.get_selected_name();
+        let dep_name = self.dep_selector.get_selected_name();
+        let feature_name = self.feature_selector.get_selected_name();
+
+        debug!("reloading the project");
+        self.document = Document::new(self.document.is_frozen())?;
+        self.marked.clear();
+
+        if self.transitive.is_some() {
+            self.load_transitive()?;
+        }
+
+        self.package_selector.data = FilterView::data_from_document(&self.document, "")?;
+        if !package_name.map_or(false, |name| self.package_selector.select(&name)) {
+            self.package_selector.selected_index = 0;
+            self.state = DisplayState::Package;
+        }
+
+        self.dep_selector.data = self.dep_data(self.package_selector.get_selected()?.name(), "")?;
+        if !dep_name.map_or(false, |name| self.dep_selector.select(&name)) {
+            self.dep_selector.selected_index = 0;
+
+            if let DisplayState::Feature = self.state {
+                self.state = DisplayState::Dep;
+            }
+        }
+
+        if let DisplayState::Feature = self.state {
+            let dep = self
+                .document
+                .get_package(self.package_selector.get_selected()?.name())?
+                .get_dep(self.dep_selector.get_selected()?.name())?;
+
+            self.feature_selector.data =
+                FilterView::data_from_dependency(dep, "", &self.feature_options);
+            if !feature_name.map_or(false, |name| self.feature_selector.select(&name)) {
+                self.feature_selector.selected_index = 0;
+            }
+        }
+
+        if !self.search_text.is_empty()
+            || matches!(
+                self.state,
+                DisplayState::AllFeatures | DisplayState::Compare
+            )
+        {
+            self.update_selected_data()?;
+        }
+
+        self.notice = Some("reloaded".to_string());
+
+        Ok(())
+    }
+
+    /// switches to the next package in the direction - in the feature selector to the next one with the same dependency
+    fn cycle_package(&mut self, direction: isize) -> Result<()> {
+        let count = self.package_selector.data.len() as isize;
+        let current = self.package_selector.selected_index as isize;
+
+        let package = self
+            .document
+            .get_package(self.package_selector.get_selected()?.name())?;
+
+        // the crate behind the selected row - e.g. `itertools` for `W itertools`
+        let dep = match self.state {
+            DisplayState::Feature => Some(
+                package
+                    .get_dep(self.dep_selector.get_selected()?.name())?
+                    .name
+                    .to_string(),
+            ),
+            _ => None,
+        };
+        let row_name = self.dep_selector.get_selected_name();
+        let feature_name = self.feature_selector.get_selected_name();
+
+        let target = (1..count)
+            .map(|step| (current + direction * step).rem_euclid(count) as usize)
+            .find_map(|index| {
+                let package = self
+                    .document
+                    .get_package(self.package_selector.data[index].name())
+                    .ok()?;
+
+                let dep_row = match &dep {
+                    Some(dep) => Some(
+                        package
+                            .dependencies
+                            .iter()
+                            .filter(|other| other.name == *dep)
+                            .map(|other| other.get_name())
+                            .sorted_by_key(|name| Some(name) != row_name.as_ref())
+                            .next()?,
+                    ),
+                    None if package.dependencies.is_empty() => return None,
+                    None => None,
+                };
+
+                Some((index, dep_row))
+            });
+
+        let Some((index, dep_row)) = target else {
+            self.notice = Some(match dep {
+                Some(dep) => format!("no other package depends on {}", dep),
+                None => "no other package has dependencies".to_string(),
+            });
+            return Ok(());
+        };
+
+        if let (Some(feature_name), DisplayState::Feature) = (&feature_name, &self.state) {
+            self.feature_positions
+                .insert(self.get_position_key()?, feature_name.to_string());
+        }
+
+        self.package_selector.selected_index = index;
+        let package_name = self.package_selector.get_selected()?.name().to_string();
+
+        match dep_row {
+            Some(dep_row) => {
+                self.dep_selector.data = self.dep_data(&package_name, "")?;
+                self.dep_selector.select(&dep_row);
+
+                self.select_selected_dep()?;
+
+                if let Some(feature_name) = feature_name {
+                    self.feature_selector.select(&feature_name);
+                }
+            }
+            None => {
+                self.dep_selector.data = self.dep_data(&package_name, &self.search_text)?;
+
+                if !row_name.map_or(false, |name| self.dep_selector.select(&name)) {
+                    self.dep_selector.selected_index = 0;
+                }
             }
         }
 
+        self.notice = Some(format!("switched to {}", package_name.trim()));
+
         Ok(())
     }
 
@@ -477,6 +1991,7 @@ impl Display {
                 }
 
                 self.search_text = "".to_string();
+                self.dep_selector.hidden_selection = None;
 
                 self.state = DisplayState::Package;
 
@@ -484,6 +1999,13 @@ impl Display {
                 Ok(RunningState::Running)
             }
             DisplayState::Feature => {
+                self.marked.clear();
+
+                if let Some(feature_name) = self.feature_selector.get_selected_name() {
+                    self.feature_positions
+                        .insert(self.get_position_key()?, feature_name);
+                }
+
                 self.search_text = "".to_string();
 
                 self.state = DisplayState::Dep;
@@ -491,8 +2013,329 @@ impl Display {
                 self.update_selected_data()?;
                 Ok(RunningState::Running)
             }
+            DisplayState::AllFeatures | DisplayState::Compare => {
+                self.search_text = "".to_string();
+
+                self.state = self.previous_state;
+
+                self.update_selected_data()?;
+                Ok(RunningState::Running)
+            }
+        }
+    }
+
+    /// opens a view returning to the current one on escape - marked features are discarded
+    fn open_view(&mut self, state: DisplayState) -> Result<()> {
+        if let (Some(feature_name), DisplayState::Feature) =
+            (self.feature_selector.get_selected_name(), self.state)
+        {
+            self.feature_positions
+                .insert(self.get_position_key()?, feature_name);
+        }
+
+        if !self.marked.is_empty() {
+            self.notice = Some(format!("discarded {} marked", self.marked.len()));
+            self.marked.clear();
+        }
+
+        // the search of the current view would rarely match a feature
+        self.search_text = "".to_string();
+
+        self.previous_state = self.state;
+        self.state = state;
+
+        self.update_selected_data()?;
+
+        Ok(())
+    }
+
+    /// opens the dependency of the selected row & selects the feature - filters hiding it are reset
+    fn open_selected_location(&mut self) -> Result<()> {
+        let location = self
+            .all_features_selector
+            .get_selected()?
+            .location()
+            .context("not a feature of a dependency")?;
+        let (package_name, dep_name, feature_name) = (
+            location.package.to_string(),
+            location.dependency.to_string(),
+            location.feature.to_string(),
+        );
+
+        self.search_text = "".to_string();
+
+        if !self.package_selector.select(&package_name) {
+            bail!("package \"{}\" could not be found", package_name);
+        }
+
+        self.select_selected_package()?;
+
+        if !self.dep_selector.select(&dep_name) {
+            self.dep_kind_filter = DependencyKindFilter::All;
+            self.dep_selector.data = self.dep_data(&package_name, "")?;
+
+            if !self.dep_selector.select(&dep_name) {
+                bail!("could not select {}", dep_name);
+            }
+        }
+
+        self.select_selected_dep()?;
+
+        if !self.feature_selector.select(&feature_name) {
+            self.feature_options.state_filter = FeatureStateFilter::All;
+            self.feature_options.collapse_defaults = false;
+            self.feature_options.collapsed_groups.clear();
+
+            self.update_selected_data()?;
+            self.feature_selector.select(&feature_name);
+        }
+
+        Ok(())
+    }
+}
+
+/// ctrl + b
+const CTRL_B: char = '\u{2}';
+/// ctrl + d
+const CTRL_D: char = '\u{4}';
+/// ctrl + f
+const CTRL_F: char = '\u{6}';
+/// ctrl + g
+const CTRL_G: char = '\u{7}';
+/// ctrl + k
+const CTRL_K: char = '\u{b}';
+/// ctrl + l
+const CTRL_L: char = '\u{c}';
+/// ctrl + n
+const CTRL_N: char = '\u{e}';
+/// ctrl + o
+const CTRL_O: char = '\u{f}';
+/// ctrl + p
+const CTRL_P: char = '\u{10}';
+/// ctrl + q
+const CTRL_Q: char = '\u{11}';
+/// ctrl + r
+const CTRL_R: char = '\u{12}';
+/// ctrl + t
+const CTRL_T: char = '\u{14}';
+/// ctrl + w
+const CTRL_W: char = '\u{17}';
+/// ctrl + u
+const CTRL_U: char = '\u{15}';
+/// ctrl + x
+const CTRL_X: char = '\u{18}';
+/// ctrl + y
+const CTRL_Y: char = '\u{19}';
+
+/// the width of the `[X]` in front of each feature
+const MARKER_WIDTH: usize = 3;
+
+/// terminals narrower than this get less indentation - e.g. a split next to an editor
+const COMPACT_WIDTH: usize = 60;
+
+/// the columns of the rows & how much space is left of the usable width
+struct Layout {
+    name: usize,
+    name_width: usize,
+    sub_features: usize,
+    sub_features_width: usize,
+}
+
+impl Layout {
+    fn new(width: usize) -> Layout {
+        let (name, sub_features) = if width < COMPACT_WIDTH {
+            (1, 2)
+        } else {
+            (2, 6)
+        };
+
+        Layout {
+            name,
+            name_width: width.saturating_sub(name),
+            sub_features,
+            sub_features_width: width.saturating_sub(sub_features),
+        }
+    }
+}
+
+/// `[X]` for enabled features, `[-]` for features enabled by another one & `[ ]` for disabled ones - features of the workspace get the 🗃️
+fn get_marker(data: &FeatureData, is_implied: bool) -> String {
+    match data.enabled_state {
+        EnabledState::Normal(true) => "[X]".to_string(),
+        EnabledState::Normal(false) if is_implied => "[-]".to_string(),
+        EnabledState::Normal(false) => "[ ]".to_string(),
+        EnabledState::Workspace => format!("{}", Emoji("🗃️", "W")),
+    }
+}
+
+/// the markers & colors of the view with their meaning
+fn get_legend(state: &DisplayState) -> Vec<(String, &'static str)> {
+    let gray = |text: &str| style(text).color256(8).to_string();
+
+    let markers = [
+        ("[X]".to_string(), "enabled"),
+        ("[-]".to_string(), "enabled by another feature"),
+        ("[ ]".to_string(), "disabled"),
+        (Emoji("🗃️", "W").to_string(), "set by the workspace"),
+        (style("[X]").green().to_string(), "default feature"),
+    ];
+
+    match state {
+        DisplayState::Package => vec![(gray("name"), "no dependencies")],
+        DisplayState::Dep => vec![
+            (gray("(1/3)"), "enabled of all features"),
+            (
+                style("+f").green().to_string(),
+                "enabled besides the defaults",
+            ),
+            (style("-f").red().to_string(), "disabled default"),
+            (gray("name"), "no features"),
+            (Emoji("🧪", "dev").to_string(), "dev-dependency"),
+            (Emoji("🛠️", "build").to_string(), "build-dependency"),
+            (
+                Emoji("🧩", "features").to_string(),
+                "features of the package",
+            ),
+            (Emoji("🗃️", "W").to_string(), "inherited from the workspace"),
+            (gray("(optional)"), "optional dependency"),
+            (gray("(transitive)"), "not in the manifest yet"),
+        ],
+        DisplayState::Feature => markers
+            .into_iter()
+            .chain([
+                (style("[~]").yellow().to_string(), "marked"),
+                (gray("name"), "required by an enabled feature"),
+                (
+                    Emoji("📦", "dep:").to_string(),
+                    "enables an optional dependency",
+                ),
+                (gray("(custom)"), "unknown to the dependency"),
+                ("└".to_string(), "sub features of the selected feature"),
+            ])
+            .collect(),
+        DisplayState::AllFeatures => markers.into_iter().collect(),
+        DisplayState::Compare => markers
+            .into_iter()
+            .chain([(gray("(only left)"), "the other dependency does not have it")])
+            .collect(),
+    }
+}
+
+/// puts as many entries into each line as fit the width - entries wider than the width are cut
+fn wrap_legend(entries: &[(String, &str)], width: usize) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+
+    for (symbol, meaning) in entries {
+        let entry = format!("{} {}", symbol, style(meaning).color256(8));
+
+        match lines.last_mut() {
+            Some(line) if measure_text_width(line) + 2 + measure_text_width(&entry) <= width => {
+                line.push_str("  ");
+                line.push_str(&entry);
+            }
+            _ => lines.push(fit_to_width(&entry, width)),
+        }
+    }
+
+    lines
+}
+
+/// cuts the text to the width - ending with `…` if it was cut
+fn fit_to_width(text: &str, width: usize) -> String {
+    // `truncate_str` already cuts a text filling the whole width & can not handle a width of 0
+    if measure_text_width(text) <= width {
+        text.to_string()
+    } else if width == 0 {
+        String::new()
+    } else {
+        truncate_str(text, width, "…").to_string()
+    }
+}
+
+/// toggles each feature which is not in the wanted state yet - a feature toggled by an earlier one in cascade mode is skipped
+fn apply_changes(
+    dependency: &mut Dependency,
+    changes: &[(String, bool)],
+    explicit_only: bool,
+) -> Result<()> {
+    for (feature_name, enable) in changes {
+        let is_enabled = dependency
+            .get_feature(feature_name)
+            .context(format!("could not find {}", feature_name))?
+            .is_enabled();
+
+        if is_enabled == *enable {
+            continue;
+        }
+
+        if explicit_only {
+            dependency.toggle_feature_explicit(feature_name)?;
+        } else {
+            dependency.toggle_feature(feature_name)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// disables the previously enabled features conflicting with the newly enabled ones - returns a message per conflict
+///
+/// conflicts which can not be resolved (e.g. both features were enabled at once) are only reported
+fn resolve_conflicts(
+    dependency: &mut Dependency,
+    previously_enabled: &HashSet<String>,
+) -> Result<Vec<String>> {
+    let mut messages = vec![];
+
+    for (feature_a, feature_b) in dependency.get_enabled_conflicts() {
+        let is_enabled = |name: &str| {
+            dependency
+                .get_feature(name)
+                .map_or(false, |data| data.is_enabled())
+        };
+
+        // an earlier conflict may already have disabled one of them
+        if !is_enabled(&feature_a) || !is_enabled(&feature_b) {
+            continue;
+        }
+
+        let (old, new) = match (
+            previously_enabled.contains(&feature_a),
+            previously_enabled.contains(&feature_b),
+        ) {
+            (true, false) => (feature_a, feature_b),
+            (false, true) => (feature_b, feature_a),
+            _ => {
+                messages.push(format!("{} conflicts with {}", feature_a, feature_b));
+                continue;
+            }
+        };
+
+        if dependency
+            .get_feature(&old)
+            .map_or(false, |data| data.is_toggleable())
+        {
+            dependency.disable_feature(&old)?;
+            messages.push(format!("disabled {} - it conflicts with {}", old, new));
+        } else {
+            messages.push(format!("{} conflicts with {}", new, old));
         }
     }
+
+    Ok(messages)
+}
+
+/// returns the items visible on a screen of the given height - the selected item is kept in the middle if possible
+///
+/// the first line is used by the header & `offset` lines below the items are reserved (e.g. for sub features)
+fn get_window(selected: usize, len: usize, height: usize, offset: usize) -> Range<usize> {
+    let rows = height.saturating_sub(1 + offset);
+
+    let start = (selected as isize - height as isize / 2 + 1)
+        .min(len as isize - rows as isize)
+        .max(0) as usize;
+
+    start..len.min(start + rows)
 }
 
 enum RunningState {
@@ -500,8 +2343,242 @@ enum RunningState {
     Finished,
 }
 
+#[derive(Clone, Copy, PartialEq)]
 enum DisplayState {
     Package,
     Dep,
     Feature,
+    /// the features of every dependency - opened with ctrl + k
+    AllFeatures,
+    /// two dependencies side by side - opened by picking both with ctrl + p
+    Compare,
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::{
+        apply_changes, fit_to_width, get_legend, get_marker, get_window, resolve_conflicts,
+        wrap_legend, Display, DisplayState, Layout, MARKER_WIDTH,
+    };
+    use crate::project::dependency::builder::DependencyBuilder;
+    use crate::project::document::Document;
+    use crate::util::fixture_project;
+    use console::{measure_text_width, pad_str, strip_ansi_codes, style, Alignment};
+    use std::collections::HashSet;
+    use std::fs;
+
+    #[test]
+    fn window_starts_at_the_top() {
+        assert_eq!(get_window(0, 100, 10, 0), 0..9);
+        assert_eq!(get_window(3, 100, 10, 0), 0..9);
+    }
+
+    #[test]
+    fn window_ends_at_the_bottom() {
+        assert_eq!(get_window(99, 100, 10, 0), 91..100);
+        assert_eq!(get_window(97, 100, 10, 0), 91..100);
+    }
+
+    #[test]
+    fn window_centers_the_selection() {
+        assert_eq!(get_window(50, 100, 10, 0), 46..55);
+    }
+
+    #[test]
+    fn window_shows_short_lists_completely() {
+        assert_eq!(get_window(0, 3, 10, 0), 0..3);
+        assert_eq!(get_window(2, 3, 10, 0), 0..3);
+        assert_eq!(get_window(0, 0, 10, 0), 0..0);
+    }
+
+    #[test]
+    fn window_is_empty_without_space() {
+        assert_eq!(get_window(5, 10, 1, 0), 6..6);
+        assert_eq!(get_window(5, 10, 0, 0), 6..6);
+        assert_eq!(get_window(5, 10, 1, 1), 6..6);
+    }
+
+    #[test]
+    fn window_reserves_the_offset() {
+        assert_eq!(get_window(0, 100, 10, 1), 0..8);
+        assert_eq!(get_window(99, 100, 10, 1), 92..100);
+        assert_eq!(get_window(50, 100, 10, 1), 46..54);
+    }
+
+    #[test]
+    fn long_feature_names_are_cut_to_the_width() {
+        let name = "a-very-long-feature-name-which-does-not-fit";
+
+        assert_eq!(fit_to_width(name, 10), "a-very-lo…");
+        assert_eq!(measure_text_width(&fit_to_width(name, 10)), 10);
+        assert_eq!(fit_to_width("short", 10), "short");
+        assert_eq!(fit_to_width("exactly-10", 10), "exactly-10");
+        assert_eq!(fit_to_width("short", 0), "");
+    }
+
+    #[test]
+    fn styled_feature_names_are_cut_by_their_visible_width() {
+        let name = style("a-very-long-feature-name")
+            .color256(8)
+            .force_styling(true);
+        let fitted = fit_to_width(&name.to_string(), 10);
+
+        assert_eq!(measure_text_width(&fitted), 10);
+        assert_eq!(strip_ansi_codes(&fitted), "a-very-lo…");
+    }
+
+    #[test]
+    fn narrow_terminals_get_less_indentation() {
+        let layout = Layout::new(40);
+        assert_eq!((layout.name, layout.sub_features), (1, 2));
+        assert_eq!((layout.name_width, layout.sub_features_width), (39, 38));
+
+        let layout = Layout::new(99);
+        assert_eq!((layout.name, layout.sub_features), (2, 6));
+        assert_eq!((layout.name_width, layout.sub_features_width), (97, 93));
+    }
+
+    #[test]
+    fn markers_have_the_same_width() {
+        for marker in ["[X]", "[ ]", "[-]", "W", "🗃️"] {
+            assert_eq!(
+                measure_text_width(&pad_str(marker, MARKER_WIDTH, Alignment::Left, None)),
+                MARKER_WIDTH
+            );
+        }
+    }
+
+    #[test]
+    fn the_legend_is_wrapped_to_the_width() {
+        let entries = get_legend(&DisplayState::Feature);
+        let lines = wrap_legend(&entries, 40);
+
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| measure_text_width(line) <= 40));
+        assert_eq!(
+            lines
+                .iter()
+                .map(|line| line.matches("  ").count() + 1)
+                .sum::<usize>(),
+            entries.len()
+        );
+    }
+
+    #[test]
+    fn marked_features_are_applied_together() {
+        let mut dependency = DependencyBuilder::new("tokio")
+            .feature("full", &["rt", "macros"])
+            .feature("rt", &[])
+            .feature("macros", &[])
+            .feature("fs", &[])
+            .enabled(&["fs"])
+            .build()
+            .unwrap();
+
+        let changes = [
+            ("full".to_string(), true),
+            ("rt".to_string(), true),
+            ("fs".to_string(), false),
+        ];
+
+        apply_changes(&mut dependency, &changes, false).unwrap();
+
+        for (feature, enabled) in [
+            ("full", true),
+            ("rt", true),
+            ("macros", true),
+            ("fs", false),
+        ] {
+            assert_eq!(
+                dependency.get_feature(feature).unwrap().is_enabled(),
+                enabled,
+                "{}",
+                feature
+            );
+        }
+    }
+
+    #[test]
+    fn sub_features_are_marked_like_features() {
+        let mut dependency = DependencyBuilder::new("tokio")
+            .feature("full", &["rt"])
+            .feature("rt", &[])
+            .feature("fs", &[])
+            .build()
+            .unwrap();
+
+        // only `full` itself - `rt` is enabled by it
+        dependency.toggle_feature_explicit("full").unwrap();
+
+        let implied = dependency.get_implied_features();
+        let marker = |name: &str| {
+            get_marker(
+                dependency.get_feature(name).unwrap(),
+                implied.contains(name),
+            )
+        };
+
+        assert_eq!(marker("full"), "[X]");
+        assert_eq!(marker("rt"), "[-]");
+        assert_eq!(marker("fs"), "[ ]");
+    }
+
+    fn runtimes() -> DependencyBuilder {
+        DependencyBuilder::new("sqlx")
+            .feature("runtime-tokio", &[])
+            .feature("runtime-async-std", &[])
+            .conflict(&["runtime-tokio", "runtime-async-std"])
+    }
+
+    #[test]
+    fn enabling_a_conflicting_feature_disables_the_other() {
+        let mut dependency = runtimes().enabled(&["runtime-async-std"]).build().unwrap();
+        let previously_enabled = HashSet::from(["runtime-async-std".to_string()]);
+
+        dependency.enable_feature("runtime-tokio").unwrap();
+
+        assert_eq!(
+            resolve_conflicts(&mut dependency, &previously_enabled).unwrap(),
+            ["disabled runtime-async-std - it conflicts with runtime-tokio"]
+        );
+        assert!(!dependency
+            .get_feature("runtime-async-std")
+            .unwrap()
+            .is_enabled());
+    }
+
+    #[test]
+    fn features_enabled_together_are_only_reported() {
+        let mut dependency = runtimes().build().unwrap();
+
+        dependency.enable_feature("runtime-tokio").unwrap();
+        dependency.enable_feature("runtime-async-std").unwrap();
+
+        assert_eq!(
+            resolve_conflicts(&mut dependency, &HashSet::new()).unwrap(),
+            ["runtime-async-std conflicts with runtime-tokio"]
+        );
+        assert_eq!(dependency.get_enabled_conflicts().len(), 1);
+    }
+
+    #[test]
+    fn selecting_a_dependency_without_features_stays_in_the_dependencies() {
+        let dir = fixture_project("dep = \"0.1\"");
+
+        fs::write(
+            dir.path().join("dep/Cargo.toml"),
+            "[package]\nname = \"dep\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+        )
+        .unwrap();
+
+        let document = Document::from_manifest_path(dir.path().join("Cargo.toml"), true).unwrap();
+        let mut display = Display::new(document).unwrap();
+
+        display.set_selected_dep(None, "dep".to_string()).unwrap();
+
+        assert!(matches!(display.state, DisplayState::Dep));
+        assert_eq!(display.notice.as_deref(), Some("dep has no features"));
+    }
 }
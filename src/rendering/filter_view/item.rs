@@ -1,3 +1,4 @@
+use crate::parsing::transitive::TransitiveDependency;
 use crate::project::dependency::Dependency;
 use crate::project::package::Package;
 use crate::rendering::search::highlight_search;
@@ -6,6 +7,17 @@ use console::style;
 pub struct FilterViewItem {
     name: String,
     display_name: String,
+    is_group: bool,
+    is_defaults: bool,
+    is_transitive: bool,
+    location: Option<FeatureLocation>,
+}
+
+/// the dependency a row of the search across all dependencies belongs to
+pub struct FeatureLocation {
+    pub package: String,
+    pub dependency: String,
+    pub feature: String,
 }
 
 impl FilterViewItem {
@@ -17,6 +29,10 @@ impl FilterViewItem {
                 &highlighted_letters,
                 dep.dependencies.is_empty(),
             ),
+            is_group: false,
+            is_defaults: false,
+            is_transitive: false,
+            location: None,
         }
     }
 
@@ -24,10 +40,36 @@ impl FilterViewItem {
         let mut display_name =
             highlight_search(&dep.get_name(), &highlighted_letters, !dep.has_features());
 
+        if dep.has_features() {
+            display_name.push_str(
+                &style(format!(
+                    " ({}/{})",
+                    dep.get_enabled_features_count(),
+                    dep.get_features_count()
+                ))
+                .color256(8)
+                .to_string(),
+            );
+        }
+
+        let (added, removed) = dep.get_default_delta();
+
+        for feature in added {
+            display_name.push_str(&format!(" {}", style(format!("+{}", feature)).green()));
+        }
+
+        for feature in removed {
+            display_name.push_str(&format!(" {}", style(format!("-{}", feature)).red()));
+        }
+
         if let Some(rename) = &dep.rename {
             display_name.push_str(&style(format!(" ({})", rename)).color256(8).to_string());
         }
 
+        if dep.optional {
+            display_name.push_str(&style(" (optional)").color256(8).to_string());
+        }
+
         if let Some(comment) = &dep.comment {
             display_name.push_str(&style(format!(" ({})", comment)).color256(8).to_string());
         }
@@ -35,6 +77,29 @@ impl FilterViewItem {
         Self {
             name: dep.get_name(),
             display_name,
+            is_group: false,
+            is_defaults: false,
+            is_transitive: false,
+            location: None,
+        }
+    }
+
+    pub fn from_transitive(dep: &TransitiveDependency, highlighted_letters: Vec<usize>) -> Self {
+        let mut display_name = highlight_search(&dep.name, &highlighted_letters, true);
+
+        display_name.push_str(
+            &style(format!(" {} (transitive)", dep.version))
+                .color256(8)
+                .to_string(),
+        );
+
+        Self {
+            name: dep.name.to_string(),
+            display_name,
+            is_group: false,
+            is_defaults: false,
+            is_transitive: true,
+            location: None,
         }
     }
 
@@ -42,6 +107,76 @@ impl FilterViewItem {
         Self {
             name: name.to_string(),
             display_name: highlight_search(name, &highlighted_letters, false),
+            is_group: false,
+            is_defaults: false,
+            is_transitive: false,
+            location: None,
+        }
+    }
+
+    /// `dependency: feature` - followed by the package in a workspace
+    pub fn from_located_feature(
+        package: &Package,
+        dep: &Dependency,
+        feature: &str,
+        highlighted_letters: Vec<usize>,
+        show_package: bool,
+    ) -> Self {
+        let mut display_name = format!(
+            "{}: {}",
+            dep.get_name(),
+            highlight_search(feature, &highlighted_letters, false)
+        );
+
+        if show_package {
+            display_name.push_str(
+                &style(format!(" ({})", package.name))
+                    .color256(8)
+                    .to_string(),
+            );
+        }
+
+        Self {
+            name: format!("{}:{}:{}", package.name, dep.get_name(), feature),
+            display_name,
+            is_group: false,
+            is_defaults: false,
+            is_transitive: false,
+            location: Some(FeatureLocation {
+                package: package.name.to_string(),
+                dependency: dep.get_name(),
+                feature: feature.to_string(),
+            }),
+        }
+    }
+
+    pub fn from_group(name: &str, size: usize, is_collapsed: bool) -> Self {
+        let marker = if is_collapsed { "▸" } else { "▾" };
+
+        Self {
+            name: name.to_string(),
+            display_name: style(format!("{} ({} features) {}", name, size, marker))
+                .bold()
+                .to_string(),
+            is_group: true,
+            is_defaults: false,
+            is_transitive: false,
+            location: None,
+        }
+    }
+
+    /// the collapsed default features - expanding it shows them again
+    pub fn from_defaults(size: usize) -> Self {
+        Self {
+            name: "default".to_string(),
+            display_name: style(format!("default ({} features) ▸", size))
+                .green()
+                .bold()
+                .to_string(),
+            is_group: true,
+            is_defaults: true,
+            is_transitive: false,
+            location: None,
         }
     }
 
@@ -52,4 +187,20 @@ impl FilterViewItem {
     pub fn display_name(&self) -> &str {
         &self.display_name
     }
+
+    pub fn is_group(&self) -> bool {
+        self.is_group
+    }
+
+    pub fn is_defaults(&self) -> bool {
+        self.is_defaults
+    }
+
+    pub fn is_transitive(&self) -> bool {
+        self.is_transitive
+    }
+
+    pub fn location(&self) -> Option<&FeatureLocation> {
+        self.location.as_ref()
+    }
 }
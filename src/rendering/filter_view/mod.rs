@@ -1,21 +1,184 @@
-use crate::project::dependency::Dependency;
+use crate::parsing::transitive::TransitiveDependency;
+use crate::project::dependency::feature::FeatureData;
+use crate::project::dependency::{Dependency, DependencyType};
 use crate::project::document::Document;
 use crate::project::package::Package;
 use crate::rendering::filter_view::item::FilterViewItem;
+use crate::rendering::search::fuzzy_match;
 use color_eyre::eyre::ContextCompat;
 use fuzzy_matcher::skim::SkimMatcherV2;
 use itertools::Itertools;
 use std::cmp::Ordering;
+use std::collections::HashSet;
 
 pub mod item;
 
 pub struct FilterView {
     pub selected_index: usize,
     pub data: Vec<FilterViewItem>,
+    /// the selected item while it is hidden by the search - selected again once it reappears
+    pub hidden_selection: Option<String>,
+}
+
+#[derive(Default)]
+pub struct FeatureViewOptions {
+    pub group_by_prefix: bool,
+    pub collapsed_groups: HashSet<String>,
+    pub state_filter: FeatureStateFilter,
+    /// toggle only the selected feature instead of cascading to its sub features and dependents
+    pub explicit_only: bool,
+    /// show the default features as a single row - only without search & grouping
+    pub collapse_defaults: bool,
+    pub sort: FeatureSort,
+}
+
+/// the order of the features without search & grouping
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum FeatureSort {
+    #[default]
+    DefaultsFirst,
+    Alphabetical,
+    EnabledFirst,
+}
+
+impl FeatureSort {
+    pub fn next(self) -> Self {
+        match self {
+            FeatureSort::DefaultsFirst => FeatureSort::Alphabetical,
+            FeatureSort::Alphabetical => FeatureSort::EnabledFirst,
+            FeatureSort::EnabledFirst => FeatureSort::DefaultsFirst,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FeatureSort::DefaultsFirst => "defaults first",
+            FeatureSort::Alphabetical => "alphabetical",
+            FeatureSort::EnabledFirst => "enabled first",
+        }
+    }
+
+    fn compare(
+        &self,
+        (name_a, data_a): (&String, &FeatureData),
+        (name_b, data_b): (&String, &FeatureData),
+    ) -> Ordering {
+        let first = match self {
+            FeatureSort::DefaultsFirst => data_b.is_default.cmp(&data_a.is_default),
+            FeatureSort::Alphabetical => Ordering::Equal,
+            FeatureSort::EnabledFirst => data_b.is_enabled().cmp(&data_a.is_enabled()),
+        };
+
+        first.then_with(|| name_a.cmp(name_b))
+    }
+}
+
+/// limits the displayed features by their state - combined with the search
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum FeatureStateFilter {
+    #[default]
+    All,
+    Enabled,
+    NonDefault,
+}
+
+impl FeatureStateFilter {
+    pub fn next(self) -> Self {
+        match self {
+            FeatureStateFilter::All => FeatureStateFilter::Enabled,
+            FeatureStateFilter::Enabled => FeatureStateFilter::NonDefault,
+            FeatureStateFilter::NonDefault => FeatureStateFilter::All,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            FeatureStateFilter::All => "all",
+            FeatureStateFilter::Enabled => "enabled",
+            FeatureStateFilter::NonDefault => "non-default",
+        }
+    }
+
+    fn matches(&self, data: &FeatureData) -> bool {
+        match self {
+            FeatureStateFilter::All => true,
+            FeatureStateFilter::Enabled => data.is_enabled(),
+            FeatureStateFilter::NonDefault => !data.is_default,
+        }
+    }
+}
+
+/// limits the displayed dependencies by their kind - combined with the search
+#[derive(Default, Clone, Copy, PartialEq)]
+pub enum DependencyKindFilter {
+    #[default]
+    All,
+    Normal,
+    Development,
+    Build,
+}
+
+impl DependencyKindFilter {
+    pub fn next(self) -> Self {
+        match self {
+            DependencyKindFilter::All => DependencyKindFilter::Normal,
+            DependencyKindFilter::Normal => DependencyKindFilter::Development,
+            DependencyKindFilter::Development => DependencyKindFilter::Build,
+            DependencyKindFilter::Build => DependencyKindFilter::All,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            DependencyKindFilter::All => "all",
+            DependencyKindFilter::Normal => "normal",
+            DependencyKindFilter::Development => "dev",
+            DependencyKindFilter::Build => "build",
+        }
+    }
+
+    fn matches(&self, dependency: &Dependency) -> bool {
+        match self {
+            DependencyKindFilter::All => true,
+            DependencyKindFilter::Normal => matches!(dependency.kind, DependencyType::Normal),
+            DependencyKindFilter::Development => {
+                matches!(dependency.kind, DependencyType::Development)
+            }
+            DependencyKindFilter::Build => matches!(dependency.kind, DependencyType::Build),
+        }
+    }
+
+    /// transitive dependencies are added as normal ones
+    pub fn includes_transitive(&self) -> bool {
+        matches!(
+            self,
+            DependencyKindFilter::All | DependencyKindFilter::Normal
+        )
+    }
+}
+
+impl FeatureViewOptions {
+    pub fn toggle_group(&mut self, group: &str) {
+        if !self.collapsed_groups.remove(group) {
+            self.collapsed_groups.insert(group.to_string());
+        }
+    }
 }
 
 impl FilterView {
+    pub fn new(data: Vec<FilterViewItem>) -> Self {
+        Self {
+            selected_index: 0,
+            data,
+            hidden_selection: None,
+        }
+    }
+
     pub fn shift(&mut self, shift: isize) {
+        if shift != 0 {
+            self.hidden_selection = None;
+        }
+
         if !self.has_data() {
             self.selected_index = 0;
             return;
@@ -41,32 +204,121 @@ impl FilterView {
         !self.data.is_empty()
     }
 
-    pub fn data_from_dependency(dependency: &Dependency, filter: &str) -> Vec<FilterViewItem> {
+    /// selects the item with the given name - returns false if there is none
+    pub fn select(&mut self, name: &str) -> bool {
+        match self.data.iter().position(|item| item.name() == name) {
+            None => false,
+            Some(index) => {
+                self.selected_index = index;
+                self.hidden_selection = None;
+                true
+            }
+        }
+    }
+
+    /// selects the item at the index - returns false if it is out of range
+    pub fn select_index(&mut self, index: usize) -> bool {
+        if index >= self.data.len() {
+            return false;
+        }
+
+        self.selected_index = index;
+        self.hidden_selection = None;
+        true
+    }
+
+    pub fn get_selected_name(&self) -> Option<String> {
+        self.get_selected().ok().map(|item| item.name().to_string())
+    }
+
+    /// replaces the data while keeping the selected item - the first item is selected while it is missing
+    pub fn set_data(&mut self, data: Vec<FilterViewItem>) {
+        let selected = self
+            .hidden_selection
+            .take()
+            .or_else(|| self.get_selected_name());
+
+        self.data = data;
+
+        if let Some(name) = selected {
+            if !self.select(&name) {
+                self.selected_index = 0;
+                self.hidden_selection = Some(name);
+            }
+        } else {
+            self.selected_index = 0;
+        }
+    }
+
+    pub fn data_from_dependency(
+        dependency: &Dependency,
+        filter: &str,
+        options: &FeatureViewOptions,
+    ) -> Vec<FilterViewItem> {
         let features = dependency
             .features
             .iter()
-            .filter(|feature| feature.0 != "default");
+            .filter(|feature| feature.0 != "default")
+            .filter(|(_, data)| options.state_filter.matches(data));
 
-        if filter.is_empty() {
-            features
-                .sorted_by(|(name_a, data_a), (name_b, data_b)| {
-                    if data_a.is_default && !data_b.is_default {
-                        return Ordering::Less;
-                    }
+        if filter.is_empty() && options.group_by_prefix {
+            let mut data = vec![];
 
-                    if data_b.is_default && !data_a.is_default {
-                        return Ordering::Greater;
-                    }
+            for (group, features) in &features
+                .map(|(name, _)| name)
+                .sorted_by_key(|name| (feature_group(name), *name))
+                .chunk_by(|name| feature_group(name))
+            {
+                let features = features.collect_vec();
 
-                    name_a.cmp(name_b)
-                })
-                .map(|(name, _)| FilterViewItem::from_feature(name, vec![]))
-                .collect()
+                if features.len() == 1 {
+                    data.push(FilterViewItem::from_feature(features[0], vec![]));
+                    continue;
+                }
+
+                let is_collapsed = options.collapsed_groups.contains(group);
+
+                data.push(FilterViewItem::from_group(
+                    group,
+                    features.len(),
+                    is_collapsed,
+                ));
+
+                if !is_collapsed {
+                    data.extend(
+                        features
+                            .into_iter()
+                            .map(|name| FilterViewItem::from_feature(name, vec![])),
+                    );
+                }
+            }
+
+            data
+        } else if filter.is_empty() {
+            let (defaults, features): (Vec<_>, Vec<_>) = features
+                .sorted_by(|a, b| options.sort.compare(*a, *b))
+                .partition(|(_, data)| options.collapse_defaults && data.is_default);
+
+            let mut data = vec![];
+
+            if !defaults.is_empty() {
+                data.push(FilterViewItem::from_defaults(defaults.len()));
+            }
+
+            data.extend(
+                features
+                    .into_iter()
+                    .map(|(name, _)| FilterViewItem::from_feature(name, vec![])),
+            );
+
+            data
         } else {
             let matcher = SkimMatcherV2::default();
 
             features
-                .filter_map(|(name, _)| matcher.fuzzy(name, filter, true).map(|some| (name, some)))
+                .filter_map(|(name, _)| {
+                    fuzzy_match(&matcher, name, filter).map(|some| (name, some))
+                })
                 .sorted_by(|(_, fuzzy_a), (_, fuzzy_b)| fuzzy_a.0.cmp(&fuzzy_b.0).reverse())
                 .map(|(name, fuzzy)| (name, fuzzy.1))
                 .map(|(name, indexes)| FilterViewItem::from_feature(name, indexes))
@@ -77,23 +329,24 @@ impl FilterView {
     pub fn data_from_package(
         package: &Package,
         filter: &str,
+        kind_filter: DependencyKindFilter,
     ) -> color_eyre::Result<Vec<FilterViewItem>> {
+        let dependencies = package
+            .dependencies
+            .iter()
+            .filter(|dependency| kind_filter.matches(dependency));
+
         let deps = if filter.is_empty() {
-            package
-                .dependencies
-                .iter()
-                .sorted_by(|dependency_a, dependency_b| dependency_a.name.cmp(&dependency_b.name))
+            dependencies
+                .sorted_by_key(|dependency| (!dependency.is_own_features(), &dependency.name))
                 .map(|dependency| FilterViewItem::from_dependency(dependency, vec![]))
                 .collect()
         } else {
             let matcher = SkimMatcherV2::default();
 
-            package
-                .dependencies
-                .iter()
+            dependencies
                 .filter_map(|dependency| {
-                    matcher
-                        .fuzzy(&dependency.get_name(), filter, true)
+                    fuzzy_match(&matcher, &dependency.get_name(), filter)
                         .map(|fuzzy_result| (dependency, fuzzy_result))
                 })
                 .sorted_by(|(_, fuzzy_a), (_, fuzzy_b)| fuzzy_a.0.cmp(&fuzzy_b.0).reverse())
@@ -105,6 +358,121 @@ impl FilterView {
         Ok(deps)
     }
 
+    /// the transitive dependencies listed after the direct ones - only the ones matching the filter
+    pub fn data_from_transitive(
+        dependencies: &[TransitiveDependency],
+        filter: &str,
+    ) -> Vec<FilterViewItem> {
+        if filter.is_empty() {
+            return dependencies
+                .iter()
+                .map(|dependency| FilterViewItem::from_transitive(dependency, vec![]))
+                .collect();
+        }
+
+        let matcher = SkimMatcherV2::default();
+
+        dependencies
+            .iter()
+            .filter_map(|dependency| {
+                fuzzy_match(&matcher, &dependency.name, filter)
+                    .map(|fuzzy_result| (dependency, fuzzy_result))
+            })
+            .sorted_by(|(_, fuzzy_a), (_, fuzzy_b)| fuzzy_a.0.cmp(&fuzzy_b.0).reverse())
+            .map(|(dependency, fuzzy)| FilterViewItem::from_transitive(dependency, fuzzy.1))
+            .collect()
+    }
+
+    /// the features of both dependencies on one axis - features only one of them has are included
+    pub fn data_from_comparison(
+        left: &Dependency,
+        right: &Dependency,
+        filter: &str,
+    ) -> Vec<FilterViewItem> {
+        let features = left
+            .features
+            .keys()
+            .chain(right.features.keys())
+            .filter(|feature| *feature != "default")
+            .sorted()
+            .dedup();
+
+        if filter.is_empty() {
+            return features
+                .map(|feature| FilterViewItem::from_feature(feature, vec![]))
+                .collect();
+        }
+
+        let matcher = SkimMatcherV2::default();
+
+        features
+            .filter_map(|feature| {
+                fuzzy_match(&matcher, feature, filter).map(|fuzzy_result| (feature, fuzzy_result))
+            })
+            .sorted_by(|(_, fuzzy_a), (_, fuzzy_b)| fuzzy_a.0.cmp(&fuzzy_b.0).reverse())
+            .map(|(feature, fuzzy)| FilterViewItem::from_feature(feature, fuzzy.1))
+            .collect()
+    }
+
+    /// the features of every dependency of the packages - sorted by package, dependency & feature without a filter
+    pub fn data_from_all_features(
+        packages: &[Package],
+        filter: &str,
+        show_package: bool,
+    ) -> Vec<FilterViewItem> {
+        let features = packages
+            .iter()
+            .sorted_by(|package_a, package_b| package_a.name.cmp(&package_b.name))
+            .flat_map(|package| {
+                package
+                    .dependencies
+                    .iter()
+                    .filter(|dependency| dependency.has_features())
+                    .sorted_by_key(|dependency| (!dependency.is_own_features(), &dependency.name))
+                    .flat_map(move |dependency| {
+                        dependency
+                            .features
+                            .keys()
+                            .filter(|feature| *feature != "default")
+                            .sorted()
+                            .map(move |feature| (package, dependency, feature))
+                    })
+            });
+
+        if filter.is_empty() {
+            return features
+                .map(|(package, dependency, feature)| {
+                    FilterViewItem::from_located_feature(
+                        package,
+                        dependency,
+                        feature,
+                        vec![],
+                        show_package,
+                    )
+                })
+                .collect();
+        }
+
+        let matcher = SkimMatcherV2::default();
+
+        features
+            .filter_map(|(package, dependency, feature)| {
+                fuzzy_match(&matcher, feature, filter)
+                    .map(|fuzzy_result| (package, dependency, feature, fuzzy_result))
+            })
+            .sorted_by(|(_, _, _, fuzzy_a), (_, _, _, fuzzy_b)| fuzzy_a.0.cmp(&fuzzy_b.0).reverse())
+            .map(|(package, dependency, feature, fuzzy)| {
+                FilterViewItem::from_located_feature(
+                    package,
+                    dependency,
+                    feature,
+                    fuzzy.1,
+                    show_package,
+                )
+            })
+            .collect()
+    }
+
     pub fn data_from_document(
         document: &Document,
         filter: &str,
@@ -123,8 +491,7 @@ impl FilterView {
                 .get_packages()
                 .iter()
                 .filter_map(|package| {
-                    matcher
-                        .fuzzy(&package.name, filter, true)
+                    fuzzy_match(&matcher, &package.name, filter)
                         .map(|fuzzy_result| (package, fuzzy_result))
                 })
                 .sorted_by(|(_, fuzzy_a), (_, fuzzy_b)| fuzzy_a.0.cmp(&fuzzy_b.0).reverse())
@@ -136,3 +503,220 @@ impl FilterView {
         Ok(packages)
     }
 }
+
+/// returns the common prefix used to group features like `rt-multi-thread` and `rt-core`
+fn feature_group(name: &str) -> &str {
+    name.split(['-', '_']).next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::project::dependency::builder::DependencyBuilder;
+
+    fn names(data: &[FilterViewItem]) -> Vec<&str> {
+        data.iter().map(|item| item.name()).collect()
+    }
+
+    fn dependency() -> Dependency {
+        DependencyBuilder::new("serde")
+            .feature("default", &["std", "alloc"])
+            .feature("std", &[])
+            .feature("alloc", &[])
+            .feature("derive", &[])
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn collapses_the_default_features_into_one_row() {
+        let options = FeatureViewOptions {
+            collapse_defaults: true,
+            ..Default::default()
+        };
+
+        let data = FilterView::data_from_dependency(&dependency(), "", &options);
+
+        assert_eq!(names(&data), ["default", "derive"]);
+        assert!(data[0].is_group() && data[0].is_defaults());
+    }
+
+    #[test]
+    fn sorts_the_features_by_the_selected_order() {
+        let mut dependency = dependency();
+        dependency.toggle_feature("derive").unwrap();
+        dependency.toggle_feature("alloc").unwrap();
+
+        let sorted_by = |sort| {
+            let options = FeatureViewOptions {
+                sort,
+                ..Default::default()
+            };
+
+            names(&FilterView::data_from_dependency(&dependency, "", &options))
+                .into_iter()
+                .map(|name| name.to_string())
+                .collect_vec()
+        };
+
+        assert_eq!(
+            sorted_by(FeatureSort::DefaultsFirst),
+            ["alloc", "std", "derive"]
+        );
+        assert_eq!(
+            sorted_by(FeatureSort::Alphabetical),
+            ["alloc", "derive", "std"]
+        );
+        assert_eq!(
+            sorted_by(FeatureSort::EnabledFirst),
+            ["derive", "std", "alloc"]
+        );
+    }
+
+    fn items(names: &[&str]) -> Vec<FilterViewItem> {
+        names
+            .iter()
+            .map(|name| FilterViewItem::from_feature(name, vec![]))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_selected_item_when_the_data_changes() {
+        let mut view = FilterView::new(items(&["a", "b", "c"]));
+        view.select("b");
+
+        view.set_data(items(&["b", "c"]));
+
+        assert_eq!(view.get_selected_name().unwrap(), "b");
+    }
+
+    #[test]
+    fn selects_a_hidden_item_again_once_it_reappears() {
+        let mut view = FilterView::new(items(&["a", "b", "c"]));
+        view.select("b");
+
+        view.set_data(items(&["c"]));
+        assert_eq!(view.get_selected_name().unwrap(), "c");
+
+        view.set_data(items(&["a", "b", "c"]));
+        assert_eq!(view.get_selected_name().unwrap(), "b");
+    }
+
+    #[test]
+    fn selects_an_item_by_index() {
+        let mut view = FilterView::new(items(&["a", "b", "c"]));
+
+        assert!(view.select_index(2));
+        assert_eq!(view.get_selected_name().unwrap(), "c");
+
+        assert!(!view.select_index(3));
+        assert_eq!(view.get_selected_name().unwrap(), "c");
+    }
+
+    #[test]
+    fn moving_forgets_the_hidden_item() {
+        let mut view = FilterView::new(items(&["a", "b", "c"]));
+        view.select("b");
+
+        view.set_data(items(&["a", "c"]));
+        view.shift(1);
+
+        view.set_data(items(&["a", "b", "c"]));
+        assert_eq!(view.get_selected_name().unwrap(), "c");
+    }
+
+    #[test]
+    fn filters_dependencies_by_kind() {
+        let package = Package {
+            name: "a".to_string(),
+            manifest_path: "a/Cargo.toml".to_string(),
+            dependencies: vec![
+                DependencyBuilder::new("serde").build().unwrap(),
+                DependencyBuilder::new("insta")
+                    .kind(DependencyType::Development)
+                    .build()
+                    .unwrap(),
+                DependencyBuilder::new("cc")
+                    .kind(DependencyType::Build)
+                    .build()
+                    .unwrap(),
+            ],
+            keep: Default::default(),
+        };
+
+        let names_of = |filter| {
+            FilterView::data_from_package(&package, "", filter)
+                .unwrap()
+                .iter()
+                .map(|item| item.name().to_string())
+                .collect_vec()
+        };
+
+        assert_eq!(
+            names_of(DependencyKindFilter::All),
+            ["build cc", "dev insta", "serde"]
+        );
+        assert_eq!(names_of(DependencyKindFilter::Normal), ["serde"]);
+        assert_eq!(names_of(DependencyKindFilter::Development), ["dev insta"]);
+        assert_eq!(names_of(DependencyKindFilter::Build), ["build cc"]);
+    }
+
+    #[test]
+    fn searches_the_features_of_all_dependencies() {
+        let package = Package {
+            name: "a".to_string(),
+            manifest_path: "a/Cargo.toml".to_string(),
+            dependencies: vec![
+                dependency(),
+                DependencyBuilder::new("tokio")
+                    .feature("default", &[])
+                    .feature("rt", &[])
+                    .feature("serde", &[])
+                    .build()
+                    .unwrap(),
+                DependencyBuilder::new("cc").build().unwrap(),
+            ],
+            keep: Default::default(),
+        };
+
+        let data = FilterView::data_from_all_features(&[package], "serde", false);
+
+        assert_eq!(names(&data), ["a:tokio:serde"]);
+
+        let location = data[0].location().unwrap();
+        assert_eq!(
+            (location.package.as_str(), location.dependency.as_str()),
+            ("a", "tokio")
+        );
+    }
+
+    #[test]
+    fn compares_the_features_on_a_shared_axis() {
+        let other = DependencyBuilder::new("serde_core")
+            .feature("default", &["std"])
+            .feature("std", &[])
+            .feature("rc", &[])
+            .build()
+            .unwrap();
+
+        let data = FilterView::data_from_comparison(&dependency(), &other, "");
+        assert_eq!(names(&data), ["alloc", "derive", "rc", "std"]);
+
+        let data = FilterView::data_from_comparison(&dependency(), &other, "rc");
+        assert_eq!(names(&data), ["rc"]);
+    }
+
+    #[test]
+    fn searching_shows_collapsed_default_features() {
+        let options = FeatureViewOptions {
+            collapse_defaults: true,
+            ..Default::default()
+        };
+
+        let data = FilterView::data_from_dependency(&dependency(), "std", &options);
+
+        assert_eq!(names(&data), ["std"]);
+    }
+}
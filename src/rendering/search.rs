@@ -1,17 +1,101 @@
 use console::style;
+use fuzzy_matcher::skim::SkimMatcherV2;
 
+/// fuzzy matches the visible text (without ansi codes) - the returned indices are char indices as expected by `highlight_search`
+pub fn fuzzy_match(matcher: &SkimMatcherV2, text: &str, filter: &str) -> Option<(i64, Vec<usize>)> {
+    matcher.fuzzy(&strip_ansi_codes(text), filter, true)
+}
+
+/// removes the escape sequences added by `console::style` (and the styled emoji fallbacks)
+fn strip_ansi_codes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|c| c.is_ascii_alphabetic());
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+/// highlights the chars at the given char indices - ansi codes already in the text are kept and not counted
 pub fn highlight_search(text: &str, highlighted_letters: &[usize], is_dark: bool) -> String {
-    text.chars()
-        .enumerate()
-        .map(|(index, c)| {
-            match (is_dark, highlighted_letters.contains(&index)) {
-                (false, true) => style(c).red().to_string(),
-                (false, false) => c.to_string(),
-                //dark red
-                (true, true) => style(c).color256(1).to_string(),
-                //light gray
-                (true, false) => style(c).color256(8).to_string(),
+    let mut highlighted = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    let mut index = 0;
+
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            highlighted.push(c);
+
+            // copy the escape sequence up to its final byte
+            for c in chars.by_ref() {
+                highlighted.push(c);
+
+                if c.is_ascii_alphabetic() {
+                    break;
+                }
             }
-        })
-        .collect()
+
+            continue;
+        }
+
+        let c = match (is_dark, highlighted_letters.contains(&index)) {
+            (false, true) => style(c).red().to_string(),
+            (false, false) => c.to_string(),
+            //dark red
+            (true, true) => style(c).color256(1).to_string(),
+            //light gray
+            (true, false) => style(c).color256(8).to_string(),
+        };
+
+        highlighted.push_str(&c);
+        index += 1;
+    }
+
+    highlighted
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+
+    fn highlighted_chars(text: &str, filter: &str) -> String {
+        console::set_colors_enabled(true);
+
+        let (_, indices) = fuzzy_match(&SkimMatcherV2::default(), text, filter).unwrap();
+
+        // every highlighted char is wrapped in its own red style
+        let red = style('x').red().to_string();
+        let (prefix, suffix) = red.split_once('x').unwrap();
+
+        highlight_search(text, &indices, false)
+            .split(prefix)
+            .skip(1)
+            .filter_map(|part| part.split_once(suffix).map(|(c, _)| c.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn highlights_multi_byte_chars() {
+        assert_eq!(highlighted_chars("größe", "öß"), "öß");
+        assert_eq!(highlighted_chars("日本語-support", "語s"), "語s");
+    }
+
+    #[test]
+    fn ignores_ansi_codes_of_the_text() {
+        let text = format!("{} serde", style("dev").color256(8));
+
+        assert_eq!(highlighted_chars(&text, "serde"), "serde");
+        assert_eq!(
+            strip_ansi_codes(&highlight_search(&text, &[0, 4], false)),
+            "dev serde"
+        );
+    }
 }
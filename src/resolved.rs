@@ -0,0 +1,319 @@
+use cargo_metadata::{DependencyKind, Metadata, Node, Package, PackageId};
+use clap::Args;
+use color_eyre::eyre::{bail, ContextCompat};
+use color_eyre::Result;
+use console::{measure_text_width, pad_str, style, Alignment, Term};
+use itertools::Itertools;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io::Write;
+
+use crate::parsing::package::get_resolved_metadata;
+use crate::project::document::Document;
+
+#[derive(Args)]
+pub struct ResolvedArgs {
+    /// only show the given dependency
+    dependency: Option<String>,
+}
+
+/// a dependency of the workspace with the features selected in the manifests & the ones cargo compiles it with
+struct Report {
+    name: String,
+    version: String,
+    selected: BTreeSet<String>,
+    resolved: BTreeSet<String>,
+    /// the packages of the dependency graph requesting each feature which is not selected
+    unified: BTreeMap<String, Vec<String>>,
+}
+
+pub fn resolved(document: Document, args: ResolvedArgs) -> Result<()> {
+    let mut term = Term::stdout();
+
+    let metadata = get_resolved_metadata(document.is_frozen())?;
+    let resolve = metadata
+        .resolve
+        .as_ref()
+        .context("cargo metadata did not resolve the dependencies")?;
+
+    let nodes: HashMap<&PackageId, &Node> =
+        resolve.nodes.iter().map(|node| (&node.id, node)).collect();
+
+    let reports = get_selected_features(&document, args.dependency.as_deref())
+        .into_iter()
+        .filter_map(|((name, version), selected)| {
+            let package = metadata
+                .packages
+                .iter()
+                .find(|package| package.name == name && package.version.to_string() == version)?;
+            let node = nodes.get(&package.id)?;
+
+            let resolved: BTreeSet<String> = node
+                .features
+                .iter()
+                .filter(|feature| *feature != "default")
+                .cloned()
+                .collect();
+
+            let requests = get_requests(&metadata, &nodes, package);
+
+            let unified = resolved
+                .iter()
+                .filter(|feature| !selected.contains(*feature))
+                .map(|feature| {
+                    let requesters = requests
+                        .iter()
+                        .filter(|(_, features)| {
+                            feature_closure(&package.features, features).contains(feature)
+                        })
+                        .map(|(requester, _)| requester.to_string())
+                        .sorted()
+                        .dedup()
+                        .collect_vec();
+
+                    (feature.to_string(), requesters)
+                })
+                .collect();
+
+            Some(Report {
+                name,
+                version,
+                selected,
+                resolved,
+                unified,
+            })
+        })
+        .collect_vec();
+
+    if reports.is_empty() {
+        match args.dependency {
+            Some(name) => bail!("no package depends on {}", name),
+            None => bail!("no dependencies were found"),
+        }
+    }
+
+    let width = reports
+        .iter()
+        .flat_map(|report| report.unified.keys())
+        .map(|name| measure_text_width(name))
+        .chain([measure_text_width("selected")])
+        .max()
+        .unwrap_or_default()
+        + 2;
+
+    for report in &reports {
+        writeln!(term, "{} {}", style(&report.name).bold(), report.version)?;
+        writeln!(
+            term,
+            "  {}{}",
+            pad_str("selected", width, Alignment::Left, None),
+            join_or_none(&report.selected)
+        )?;
+        writeln!(
+            term,
+            "  {}{}",
+            pad_str("resolved", width, Alignment::Left, None),
+            join_or_none(&report.resolved)
+        )?;
+
+        for (feature, requesters) in &report.unified {
+            writeln!(
+                term,
+                "  {}{}",
+                style(pad_str(feature, width, Alignment::Left, None)).yellow(),
+                style(if requesters.is_empty() {
+                    "enabled by the dependency graph".to_string()
+                } else {
+                    format!("requested by {}", requesters.join(", "))
+                })
+                .color256(8)
+            )?;
+        }
+
+        writeln!(term)?;
+    }
+
+    Ok(())
+}
+
+fn join_or_none(features: &BTreeSet<String>) -> String {
+    if features.is_empty() {
+        style("no features").color256(8).to_string()
+    } else {
+        features.iter().join(", ")
+    }
+}
+
+/// returns the features the workspace members enable in their manifests per dependency name & resolved version
+fn get_selected_features(
+    document: &Document,
+    name: Option<&str>,
+) -> BTreeMap<(String, String), BTreeSet<String>> {
+    let mut selected: BTreeMap<(String, String), BTreeSet<String>> = BTreeMap::new();
+
+    let dependencies = document
+        .get_packages()
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| Some(*index) != document.workspace_index())
+        .flat_map(|(_, package)| &package.dependencies)
+        .filter(|dependency| !dependency.is_own_features())
+        .filter(|dependency| name.map_or(true, |name| dependency.name == name));
+
+    for dependency in dependencies {
+        let Some(version) = &dependency.resolved_version else {
+            continue;
+        };
+
+        selected
+            .entry((dependency.name.to_string(), version.to_string()))
+            .or_default()
+            .extend(
+                dependency
+                    .features
+                    .iter()
+                    .filter(|(name, data)| *name != "default" && data.is_enabled())
+                    .map(|(name, _)| name.to_string()),
+            );
+    }
+
+    selected
+}
+
+/// returns the features every package of the dependency graph requests from the package - per requesting package
+fn get_requests(
+    metadata: &Metadata,
+    nodes: &HashMap<&PackageId, &Node>,
+    target: &Package,
+) -> Vec<(String, Vec<String>)> {
+    let mut requests = vec![];
+
+    for package in &metadata.packages {
+        // only packages which are part of the build & actually depend on the package
+        let Some(node) = nodes.get(&package.id) else {
+            continue;
+        };
+
+        if !node.deps.iter().any(|dep| dep.pkg == target.id) {
+            continue;
+        }
+
+        let is_member = metadata.workspace_members.contains(&package.id);
+
+        for dependency in &package.dependencies {
+            if dependency.name != target.name || !dependency.req.matches(&target.version) {
+                continue;
+            }
+
+            // dev-dependencies of other packages are not built
+            if !is_member && dependency.kind == DependencyKind::Development {
+                continue;
+            }
+
+            let mut features = dependency.features.clone();
+
+            if dependency.uses_default_features {
+                features.push("default".to_string());
+            }
+
+            let key = dependency.rename.as_ref().unwrap_or(&dependency.name);
+
+            features.extend(get_forwarded_features(
+                &package.features,
+                &node.features,
+                key,
+            ));
+
+            requests.push((package.name.to_string(), features));
+        }
+    }
+
+    requests
+}
+
+/// returns the features of the dependency enabled via `dependency/feature` by the enabled features of a package
+fn get_forwarded_features(
+    features: &BTreeMap<String, Vec<String>>,
+    enabled: &[String],
+    dependency: &str,
+) -> Vec<String> {
+    enabled
+        .iter()
+        .filter_map(|feature| features.get(feature))
+        .flatten()
+        .filter_map(|entry| entry.split_once('/'))
+        .filter(|(name, _)| name.trim_end_matches('?') == dependency)
+        .map(|(_, feature)| feature.to_string())
+        .collect()
+}
+
+/// returns the features together with all features they enable
+fn feature_closure(features: &BTreeMap<String, Vec<String>>, start: &[String]) -> BTreeSet<String> {
+    let mut closure = BTreeSet::new();
+    let mut stack = start.to_vec();
+
+    while let Some(feature) = stack.pop() {
+        if !closure.insert(feature.clone()) {
+            continue;
+        }
+
+        for sub_feature in features.get(&feature).into_iter().flatten() {
+            if !sub_feature.starts_with("dep:") && !sub_feature.contains('/') {
+                stack.push(sub_feature.to_string());
+            }
+        }
+    }
+
+    closure
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn features() -> BTreeMap<String, Vec<String>> {
+        BTreeMap::from([
+            ("default".to_string(), vec!["std".to_string()]),
+            (
+                "std".to_string(),
+                vec!["alloc".to_string(), "dep:libc".to_string()],
+            ),
+            ("alloc".to_string(), vec![]),
+            (
+                "serde".to_string(),
+                vec!["dep:serde".to_string(), "serde?/std".to_string()],
+            ),
+            ("derive".to_string(), vec!["serde/derive".to_string()]),
+        ])
+    }
+
+    #[test]
+    fn closure_contains_the_sub_features() {
+        assert_eq!(
+            feature_closure(&features(), &["default".to_string()])
+                .into_iter()
+                .collect_vec(),
+            ["alloc", "default", "std"]
+        );
+    }
+
+    #[test]
+    fn closure_skips_dependencies() {
+        assert_eq!(
+            feature_closure(&features(), &["serde".to_string()])
+                .into_iter()
+                .collect_vec(),
+            ["serde"]
+        );
+    }
+
+    #[test]
+    fn finds_features_forwarded_to_the_dependency() {
+        let enabled = ["serde".to_string(), "derive".to_string()];
+
+        assert_eq!(
+            get_forwarded_features(&features(), &enabled, "serde"),
+            ["std", "derive"]
+        );
+        assert!(get_forwarded_features(&features(), &enabled, "libc").is_empty());
+    }
+}
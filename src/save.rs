@@ -1,31 +1,323 @@
+use crate::project::dependency::feature::SubFeatureType;
 use crate::project::dependency::util::get_path;
+use crate::project::dependency::Dependency;
 use crate::project::document::Document;
-use crate::util::{get_mut_item_from_doc, toml_document_from_path};
-use color_eyre::eyre::{ContextCompat, Error};
+use crate::util::{
+    get_item_from_doc, get_mut_item_from_doc, implicit_table, toml_document_from_path,
+};
+use color_eyre::eyre::{eyre, ContextCompat, WrapErr};
 use std::fs;
-use toml_edit::{Array, Formatted, InlineTable, Item, Value};
+use toml_edit::{Array, DocumentMut, Formatted, InlineTable, Item, Table, TableLike, Value};
+use tracing::debug;
 
 pub fn save_dependency(
     document: &mut Document,
     package_name: &str,
     dep_name: &str,
 ) -> color_eyre::Result<()> {
-    let package = document.get_package_mut(package_name)?;
+    let explicit_default_features = document.explicit_default_features();
+
+    let package = document.get_package(package_name)?;
+    let dependency = package.get_dep(dep_name)?;
+
+    let mut doc = document.read_manifest(&package.manifest_path)?;
+    update_manifest(dependency, &mut doc, explicit_default_features)?;
+
+    // update workspace deps
+    if let Some(workspace_index) = document.workspace_index() {
+        let workspace = document.get_package_by_id(workspace_index)?;
+
+        if workspace.name == package_name {
+            document.update_workspace_deps()?;
+        }
+    }
+
+    //write updates
+    let manifest_path = document.get_package(package_name)?.manifest_path.clone();
+
+    debug!(
+        "writing {} of {} to {}{}",
+        dep_name,
+        package_name,
+        manifest_path,
+        if document.is_scratch() {
+            " - scratch"
+        } else {
+            ""
+        }
+    );
+
+    document.write_manifest(&manifest_path, doc.to_string())
+}
+
+/// returns the manifest line the dependency would be saved as - e.g. `foo = { version = "1", features = ["bar"] }`
+pub fn render_dependency(
+    document: &Document,
+    package_name: &str,
+    dep_name: &str,
+) -> color_eyre::Result<String> {
+    let package = document.get_package(package_name)?;
     let dependency = package.get_dep(dep_name)?;
 
+    let mut doc = document.read_manifest(&package.manifest_path)?;
+    let deps = update_manifest(dependency, &mut doc, document.explicit_default_features())?;
+
+    let key = if dependency.is_own_features() {
+        "default"
+    } else {
+        dependency.rename.as_ref().unwrap_or(&dependency.name)
+    };
+    let value = deps
+        .get(key)
+        .context(format!(
+            "could not find {} in dependency",
+            dependency.get_name()
+        ))?
+        .clone()
+        .into_value()
+        .map_err(|_| eyre!("could not render {}", dependency.get_name()))?;
+
+    Ok(format!("{} = {}", key, value.to_string().trim()))
+}
+
+/// returns whether saving the unchanged dependency rewrites its features - e.g. duplicates or features enabled anyway
+///
+/// entries without a `features` array are never touched - `{ version = "1" }` is not collapsed to `"1"`
+pub fn needs_cleanup(
+    document: &Document,
+    package_name: &str,
+    dep_name: &str,
+) -> color_eyre::Result<bool> {
+    let package = document.get_package(package_name)?;
+    let dependency = package.get_dep(dep_name)?;
+
+    let before = document.read_manifest(&package.manifest_path)?;
+
+    if !dependency.is_own_features() {
+        let has_features =
+            get_item_from_doc(&get_path(&dependency.kind, &dependency.target), &before)
+                .ok()
+                .and_then(|deps| deps.get(dependency.rename.as_ref().unwrap_or(&dependency.name)))
+                .and_then(|entry| entry.as_table_like())
+                .map_or(false, |entry| entry.contains_key("features"));
+
+        if !has_features {
+            return Ok(false);
+        }
+    }
+
+    let mut after = before.clone();
+    update_manifest(dependency, &mut after, document.explicit_default_features())?;
+
+    Ok(before.to_string() != after.to_string())
+}
+
+/// adds the dependency to `[dependencies]` of the manifest with the default features disabled - e.g. a transitive dependency to configure
+pub fn add_dependency(manifest_path: &str, name: &str, version: &str) -> color_eyre::Result<()> {
+    let mut doc = toml_document_from_path(manifest_path)?;
+    insert_dependency(&mut doc, name, version)?;
+
+    debug!("adding {} {} to {}", name, version, manifest_path);
+
+    fs::write(manifest_path, doc.to_string())
+        .wrap_err_with(|| format!("could not write {}", manifest_path))
+}
+
+fn insert_dependency(doc: &mut DocumentMut, name: &str, version: &str) -> color_eyre::Result<()> {
+    let deps = doc
+        .entry("dependencies")
+        .or_insert(toml_edit::table())
+        .as_table_mut()
+        .context("could not parse dependencies as a table")?;
+
+    if deps.contains_key(name) {
+        return Err(eyre!("{} is already in [dependencies]", name));
+    }
+
+    let mut entry = InlineTable::new();
+    entry.insert("version", version.into());
+    entry.insert("default-features", false.into());
+
+    deps.insert(name, Item::Value(Value::InlineTable(entry)));
+
+    Ok(())
+}
+
+/// applies the enabled features of the dependency to its entry in the manifest
+///
+/// the entry is looked up in the table matching the kind & target of the dependency - e.g. `[dev-dependencies]`
+fn update_manifest<'a>(
+    dependency: &Dependency,
+    doc: &'a mut DocumentMut,
+    explicit_default_features: bool,
+) -> color_eyre::Result<&'a mut Table> {
+    if dependency.is_own_features() {
+        let features = doc
+            .entry("features")
+            .or_insert(toml_edit::table())
+            .as_table_mut()
+            .context("could not parse features as a table")?;
+
+        update_default_features(dependency, features)?;
+
+        return Ok(features);
+    }
+
+    let path = get_path(&dependency.kind, &dependency.target);
+
+    // the table was removed after loading (e.g. in an editor) - it is created again like when adding a dependency
+    if get_item_from_doc(&path, doc).map_or(true, |deps| deps.is_none()) {
+        insert_dependency_table(dependency, doc)?;
+    }
+
+    let deps = get_mut_item_from_doc(&path, doc)?
+        .as_table_mut()
+        .context(format!("could not parse {} as a table", path))?;
+
+    update_dependency_entry(dependency, deps, explicit_default_features)?;
+
+    Ok(deps)
+}
+
+/// creates the table of the kind & target of the dependency - e.g. `[target.'cfg(unix)'.dev-dependencies]`
+fn insert_dependency_table(
+    dependency: &Dependency,
+    doc: &mut DocumentMut,
+) -> color_eyre::Result<()> {
+    let mut keys = vec![];
+
+    if let Some(target) = &dependency.target {
+        keys.push("target".to_string());
+        keys.push(target.to_string());
+    }
+
+    keys.extend(
+        get_path(&dependency.kind, &None)
+            .split('.')
+            .map(String::from),
+    );
+
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+
+    for (index, key) in keys.iter().enumerate() {
+        // only the dependencies themselves get a header - e.g. no empty `[target]`
+        let item = if index + 1 == keys.len() {
+            toml_edit::table()
+        } else {
+            implicit_table()
+        };
+
+        table = table
+            .entry(key)
+            .or_insert(item)
+            .as_table_like_mut()
+            .context(format!("could not parse {} as a table", key))?;
+    }
+
+    Ok(())
+}
+
+/// the entry of a dependency removed from the manifest after loading - written again with what is known about it
+fn new_entry(dependency: &Dependency) -> Item {
+    let mut entry = InlineTable::new();
+
+    if dependency.workspace {
+        entry.insert("workspace", true.into());
+    } else {
+        entry.insert("version", dependency.get_version().into());
+    }
+
+    if dependency.rename.is_some() {
+        entry.insert("package", dependency.name.as_str().into());
+    }
+
+    Item::Value(Value::InlineTable(entry))
+}
+
+/// writes the enabled own features into `default` - entries which are no features (e.g. `dep:foo`) are kept
+fn update_default_features(
+    dependency: &Dependency,
+    features: &mut Table,
+) -> color_eyre::Result<()> {
+    let mut features_to_enable = dependency.get_features_to_enable();
+
+    if features_to_enable.is_empty() && !features.contains_key("default") {
+        return Ok(());
+    }
+
+    let default = features
+        .entry("default")
+        .or_insert(Item::Value(Value::Array(Array::new())))
+        .as_array_mut()
+        .context("could not parse features.default as an array")?;
+
+    default.retain(|entry| {
+        let Some(name) = entry.as_str() else {
+            return true;
+        };
+
+        if SubFeatureType::from(name) != SubFeatureType::Normal {
+            return true;
+        }
+
+        match features_to_enable
+            .iter()
+            .position(|feature| feature == name)
+        {
+            Some(index) => {
+                features_to_enable.remove(index);
+                true
+            }
+            None => false,
+        }
+    });
+
+    for name in features_to_enable {
+        default.push(name);
+    }
+
+    default.fmt();
+
+    Ok(())
+}
+
+/// applies the enabled features of the dependency to its entry in the dependencies table
+///
+/// `default-features = true` is only written if `explicit_default_features` is set or the entry already contains it
+fn update_dependency_entry(
+    dependency: &Dependency,
+    deps: &mut Table,
+    explicit_default_features: bool,
+) -> color_eyre::Result<()> {
     let features_to_enable = dependency.get_features_to_enable();
 
-    let mut doc = toml_document_from_path(&package.manifest_path)?;
-    let deps = get_mut_item_from_doc(&get_path(&dependency.kind, &dependency.target), &mut doc)?;
+    let entry = deps
+        .entry(dependency.rename.as_ref().unwrap_or(&dependency.name))
+        .or_insert_with(|| new_entry(dependency));
 
-    let deps = deps.as_table_mut().context(format!(
-        "could not parse dependencies as a table - {}",
-        package.name
-    ))?;
+    // keep the version requirement as written in the manifest
+    let version = entry
+        .as_str()
+        .or_else(|| entry.get("version").and_then(|version| version.as_str()))
+        .map(|version| version.to_string())
+        .unwrap_or_else(|| dependency.get_version());
+
+    let write_default_features = !dependency.workspace
+        && (explicit_default_features
+            || entry
+                .get("default-features")
+                .and_then(|default_features| default_features.as_bool())
+                == Some(true));
+
+    // a `[dependencies.name]` table stays one - only its keys are changed
+    let is_full_table = entry.is_table();
 
     let table = match deps
         .get_mut(dependency.rename.as_ref().unwrap_or(&dependency.name))
-        .context("dependency not found")?
+        .context(format!(
+            "could not find {} in dependency",
+            dependency.get_name()
+        ))?
         .as_table_like_mut()
     {
         None => {
@@ -52,58 +344,431 @@ pub fn save_dependency(
         .get_values()
         .iter()
         .map(|(name, _)| name.first().map(|key| key.to_string()).unwrap_or_default())
-        .any(|name| !["features", "default-features", "version"].contains(&&*name));
+        .any(|name| !["features", "default-features", "version", "optional"].contains(&&*name));
 
     //check if entry has to be table or can just be string with version
-    if dependency.can_use_default() && features_to_enable.is_empty() && !has_custom_attributes {
+    if dependency.can_use_default()
+        && features_to_enable.is_empty()
+        && !has_custom_attributes
+        && !dependency.optional
+        && !write_default_features
+        && !is_full_table
+    {
         deps.insert(
             &dependency.name,
-            Item::Value(Value::String(Formatted::new(dependency.get_version()))),
+            Item::Value(Value::String(Formatted::new(version))),
         );
     } else {
-        //version
-        if !dependency.version.is_empty() && !table.contains_key("git") && !dependency.workspace {
+        //version - git & path dependencies only keep the one they already have
+        let has_source = table.contains_key("git") || table.contains_key("path");
+
+        if !dependency.version.is_empty()
+            && !has_source
+            && !dependency.workspace
+            && !table.contains_key("version")
+        {
             table.insert(
                 "version",
-                Item::Value(Value::String(Formatted::new(dependency.get_version()))),
+                Item::Value(Value::String(Formatted::new(version))),
             );
         }
 
         //features
-        let mut features = Array::new();
-
-        for name in features_to_enable {
-            features.push(Value::String(Formatted::new(name)));
-        }
-
-        if features.is_empty() {
+        if features_to_enable.is_empty() {
             table.remove("features");
         } else {
-            table.insert("features", Item::Value(Value::Array(features)));
+            update_features(table, features_to_enable);
         }
 
         //default-feature
-        if dependency.can_use_default() || dependency.workspace {
+        if !dependency.can_use_default() && !dependency.workspace {
+            set_bool(table, "default-features", false);
+        } else if write_default_features {
+            set_bool(table, "default-features", true);
+        } else {
             table.remove("default-features");
+        }
+
+        //optional
+        if dependency.optional {
+            set_bool(table, "optional", true);
         } else {
-            table.insert(
-                "default-features",
-                Item::Value(Value::Boolean(Formatted::new(false))),
-            );
+            table.remove("optional");
+        }
+
+        // keys after the last kept one would otherwise be appended after its trailing space - `{ workspace = true , ...}`
+        if let Some(table) = deps
+            .get_mut(dependency.rename.as_ref().unwrap_or(&dependency.name))
+            .and_then(|entry| entry.as_inline_table_mut())
+        {
+            table.fmt();
         }
     }
 
-    // update workspace deps
-    if let Some(workspace_index) = document.workspace_index() {
-        let workspace = document.get_package_by_id(workspace_index)?;
+    Ok(())
+}
 
-        if workspace.name == package_name {
-            document.update_workspace_deps()?;
+/// replaces the features of the entry - an unchanged list is kept as written & a list with one feature per line stays like that
+fn update_features(table: &mut dyn TableLike, features_to_enable: Vec<String>) {
+    let existing = table.get("features").and_then(|item| item.as_array());
+
+    if existing.map_or(false, |array| {
+        array
+            .iter()
+            .map(|value| value.as_str())
+            .eq(features_to_enable.iter().map(|name| Some(name.as_str())))
+    }) {
+        return;
+    }
+
+    // e.g. `"\n    "` in front of each feature if they are written one per line
+    let prefix = existing
+        .and_then(|array| array.get(0))
+        .and_then(|value| value.decor().prefix())
+        .and_then(|prefix| prefix.as_str())
+        .filter(|prefix| prefix.contains('\n'))
+        .map(|prefix| prefix.to_string());
+
+    let trailing = existing
+        .and_then(|array| array.trailing().as_str())
+        .unwrap_or("\n")
+        .to_string();
+
+    let mut features = Array::new();
+
+    for name in features_to_enable {
+        match &prefix {
+            Some(prefix) => {
+                let mut value = Value::String(Formatted::new(name));
+                value.decor_mut().set_prefix(prefix);
+
+                features.push_formatted(value);
+            }
+            None => features.push(name),
         }
     }
 
-    //write updates
-    let package = document.get_package(package_name)?;
+    if prefix.is_some() {
+        features.set_trailing_comma(true);
+        features.set_trailing(trailing);
+    }
+
+    table.insert("features", Item::Value(Value::Array(features)));
+}
+
+/// only replaces the value if it differs - a comment behind it is kept
+fn set_bool(table: &mut dyn TableLike, key: &str, value: bool) {
+    if table.get(key).and_then(|item| item.as_bool()) != Some(value) {
+        table.insert(key, Item::Value(Value::Boolean(Formatted::new(value))));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::{insert_dependency, update_manifest};
+    use crate::project::dependency::builder::DependencyBuilder;
+    use crate::project::dependency::DependencyType;
+    use toml_edit::DocumentMut;
+
+    fn update(manifest: &str, builder: DependencyBuilder) -> String {
+        update_with(manifest, builder, false)
+    }
+
+    fn update_with(
+        manifest: &str,
+        builder: DependencyBuilder,
+        explicit_default_features: bool,
+    ) -> String {
+        let mut doc: DocumentMut = manifest.parse().unwrap();
+        let dependency = builder.build().unwrap();
+
+        update_manifest(&dependency, &mut doc, explicit_default_features).unwrap();
+
+        doc.to_string()
+    }
+
+    fn serde() -> DependencyBuilder {
+        DependencyBuilder::new("serde")
+            .version("1.0.195")
+            .feature("default", &["std"])
+            .feature("std", &[])
+            .feature("derive", &[])
+    }
+
+    #[test]
+    fn keeps_the_version_requirement_of_a_string_entry() {
+        let manifest = update(
+            "[dependencies]\nserde = \"1\"\n",
+            serde().enabled(&["derive"]),
+        );
+
+        assert_eq!(
+            manifest,
+            "[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n"
+        );
+    }
+
+    #[test]
+    fn writes_the_entry_again_if_it_was_removed_since_loading() {
+        assert_eq!(
+            update("[package]\nname = \"a\"\n", serde().enabled(&["derive"])),
+            "[package]\nname = \"a\"\n\n[dependencies]\nserde = { version = \"1.0.195\", features = [\"derive\"] }\n"
+        );
+        assert_eq!(
+            update("[dependencies]\nlog = \"0.4\"\n", serde()),
+            "[dependencies]\nlog = \"0.4\"\nserde = \"1.0.195\"\n"
+        );
+    }
+
+    #[test]
+    fn writes_the_target_table_again_if_it_was_removed_since_loading() {
+        let mut dependency = serde()
+            .enabled(&["derive"])
+            .kind(DependencyType::Development)
+            .build()
+            .unwrap();
+        dependency.target = Some("cfg(unix)".parse().unwrap());
+
+        let mut doc: DocumentMut = "[package]\nname = \"a\"\n".parse().unwrap();
+        update_manifest(&dependency, &mut doc, false).unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            "[package]\nname = \"a\"\n\n[target.\"cfg(unix)\".dev-dependencies]\nserde = { version = \"1.0.195\", features = [\"derive\"] }\n"
+        );
+    }
+
+    #[test]
+    fn a_full_table_stays_a_full_table() {
+        let manifest = update(
+            "[dependencies.serde]\nversion = \"1\"\nfeatures = [\"derive\"]\n",
+            serde(),
+        );
+
+        assert_eq!(manifest, "[dependencies.serde]\nversion = \"1\"\n");
+    }
+
+    #[test]
+    fn keeps_one_feature_per_line() {
+        let manifest = update(
+            "[dependencies.serde]\nversion = \"1\"\ndefault-features = false # no std\nfeatures = [\n    \"derive\",\n]\n",
+            serde()
+                .feature("rc", &[])
+                .default_features(false)
+                .enabled(&["derive", "rc"]),
+        );
+
+        assert_eq!(
+            manifest,
+            "[dependencies.serde]\nversion = \"1\"\ndefault-features = false # no std\nfeatures = [\n    \"derive\",\n    \"rc\",\n]\n"
+        );
+    }
+
+    #[test]
+    fn keeps_the_version_requirement_of_a_table_entry() {
+        let manifest = update(
+            "[dependencies]\nserde = { version = \"~1.0\", features = [\"derive\"] }\n",
+            serde(),
+        );
+
+        assert_eq!(manifest, "[dependencies]\nserde = \"~1.0\"\n");
+    }
+
+    #[test]
+    fn only_updates_the_entry_of_the_same_kind() {
+        let manifest = "[dependencies]\nserde = \"1\"\n\n[dev-dependencies]\nserde = \"1\"\n";
+
+        let manifest = update(
+            manifest,
+            serde()
+                .kind(DependencyType::Development)
+                .enabled(&["derive"]),
+        );
+
+        assert_eq!(
+            manifest,
+            "[dependencies]\nserde = \"1\"\n\n[dev-dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n"
+        );
+
+        let manifest = update(&manifest, serde().default_features(false));
+
+        assert_eq!(
+            manifest,
+            "[dependencies]\nserde = { version = \"1\", default-features = false }\n\n[dev-dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n"
+        );
+    }
+
+    #[test]
+    fn own_features_are_written_to_default() {
+        let own_features = || {
+            DependencyBuilder::new("package")
+                .kind(DependencyType::Features)
+                .feature("default", &["a", "dep:serde"])
+                .feature("a", &[])
+                .feature("b", &["a"])
+        };
+
+        let manifest = "[features]\ndefault = [\"a\", \"dep:serde\"]\na = []\nb = [\"a\"]\n";
+
+        assert_eq!(
+            update(manifest, own_features().enabled(&["b"])),
+            "[features]\ndefault = [\"dep:serde\", \"b\"]\na = []\nb = [\"a\"]\n"
+        );
+
+        assert_eq!(
+            update(
+                "[package]\nname = \"package\"\n",
+                own_features().enabled(&["b"])
+            ),
+            "[package]\nname = \"package\"\n\n[features]\ndefault = [\"b\"]\n"
+        );
+    }
+
+    #[test]
+    fn writes_default_features_explicitly_if_configured() {
+        let manifest = update_with("[dependencies]\nserde = \"1\"\n", serde(), true);
+
+        assert_eq!(
+            manifest,
+            "[dependencies]\nserde = { version = \"1\", default-features = true }\n"
+        );
 
-    fs::write(&package.manifest_path, doc.to_string()).map_err(Error::from)
+        let manifest = update_with(
+            "[dependencies]\nserde = \"1\"\n",
+            serde().default_features(false),
+            true,
+        );
+
+        assert_eq!(
+            manifest,
+            "[dependencies]\nserde = { version = \"1\", default-features = false }\n"
+        );
+    }
+
+    #[test]
+    fn writes_the_feature_activating_an_optional_dependency() {
+        let dependency = DependencyBuilder::new("serde")
+            .version("1.0.195")
+            .feature("json", &["dep:serde_json"])
+            .enabled(&["json"])
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            dependency.get_enabled_optional_dependencies(),
+            ["serde_json"]
+        );
+
+        let mut doc: DocumentMut = "[dependencies]\nserde = \"1\"\n".parse().unwrap();
+        update_manifest(&dependency, &mut doc, false).unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            "[dependencies]\nserde = { version = \"1\", features = [\"json\"] }\n"
+        );
+    }
+
+    #[test]
+    fn never_writes_default_features_for_workspace_dependencies() {
+        let dependency = || {
+            serde()
+                .workspace()
+                .default_features(false)
+                .enabled(&["derive"])
+        };
+
+        let expected = "[dependencies]\nserde = { workspace = true, features = [\"derive\"] }\n";
+
+        assert_eq!(
+            update(
+                "[dependencies]\nserde = { workspace = true }\n",
+                dependency()
+            ),
+            expected
+        );
+        assert_eq!(
+            update_with(
+                "[dependencies]\nserde = { workspace = true }\n",
+                dependency(),
+                true
+            ),
+            expected
+        );
+
+        // cargo rejects the combination - an existing entry is removed
+        assert_eq!(
+            update(
+                "[dependencies]\nserde = { workspace = true, default-features = false }\n",
+                dependency()
+            ),
+            expected
+        );
+    }
+
+    #[test]
+    fn keeps_an_explicit_default_features_entry() {
+        let manifest = update(
+            "[dependencies]\nserde = { version = \"1\", default-features = true }\n",
+            serde().enabled(&["derive"]),
+        );
+
+        assert_eq!(
+            manifest,
+            "[dependencies]\nserde = { version = \"1\", default-features = true, features = [\"derive\"] }\n"
+        );
+    }
+
+    #[test]
+    fn keeps_the_source_of_git_and_path_dependencies() {
+        let manifest = update(
+            "[dependencies]\nserde = { git = \"https://github.com/serde-rs/serde\", branch = \"master\", rev = \"1a2b3c\" }\n",
+            serde().enabled(&["derive"]),
+        );
+
+        assert_eq!(
+            manifest,
+            "[dependencies]\nserde = { git = \"https://github.com/serde-rs/serde\", branch = \"master\", rev = \"1a2b3c\", features = [\"derive\"] }\n"
+        );
+
+        // a version next to the path is kept for publishing
+        let manifest = update(
+            "[dependencies]\nserde = { path = \"../serde\", version = \"1\" }\n",
+            serde().enabled(&["derive"]),
+        );
+
+        assert_eq!(
+            manifest,
+            "[dependencies]\nserde = { path = \"../serde\", version = \"1\", features = [\"derive\"] }\n"
+        );
+    }
+
+    #[test]
+    fn cleans_up_duplicate_and_redundant_features() {
+        let manifest = update(
+            "[dependencies]\nserde = { version = \"1\", features = [\"std\", \"derive\", \"derive\"] }\n",
+            serde().enabled(&["std", "derive", "derive"]),
+        );
+
+        assert_eq!(
+            manifest,
+            "[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n"
+        );
+    }
+
+    #[test]
+    fn adds_a_dependency_without_default_features() {
+        let mut doc: DocumentMut = "[package]\nname = \"a\"\n".parse().unwrap();
+
+        insert_dependency(&mut doc, "anstyle", "1.0.7").unwrap();
+
+        assert_eq!(
+            doc.to_string(),
+            "[package]\nname = \"a\"\n\n[dependencies]\nanstyle = { version = \"1.0.7\", default-features = false }\n"
+        );
+
+        // an existing entry is never overwritten
+        assert!(insert_dependency(&mut doc, "anstyle", "1.0.8").is_err());
+    }
 }
@@ -0,0 +1,276 @@
+use clap::Args;
+use color_eyre::eyre::bail;
+use color_eyre::Result;
+use console::{measure_text_width, pad_str, style, Alignment, Term};
+use itertools::Itertools;
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::project::dependency::{Dependency, DependencyType};
+use crate::project::document::Document;
+use crate::project::package::Package;
+
+#[derive(Args)]
+pub struct TreeArgs {
+    /// name of the dependency
+    dependency: String,
+}
+
+/// the features one package enables for the dependency
+struct Usage<'a> {
+    package: &'a str,
+    label: String,
+    dependency: &'a Dependency,
+}
+
+impl Usage<'_> {
+    fn features(&self) -> Vec<&str> {
+        self.dependency
+            .features
+            .iter()
+            .filter(|(name, data)| *name != "default" && data.is_enabled())
+            .map(|(name, _)| name.as_str())
+            .sorted()
+            .collect()
+    }
+}
+
+pub fn tree(document: Document, args: TreeArgs) -> Result<()> {
+    let mut term = Term::stdout();
+
+    let members = document
+        .get_packages()
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| Some(*index) != document.workspace_index())
+        .map(|(_, package)| package);
+
+    let usages = get_usages(members, &args.dependency);
+
+    if usages.is_empty() {
+        bail!("no package depends on {}", args.dependency);
+    }
+
+    let unified = unify(&usages);
+
+    let width = usages
+        .iter()
+        .map(|usage| measure_text_width(&usage.label))
+        .chain(unified.keys().map(|name| measure_text_width(name)))
+        .chain([measure_text_width("unified")])
+        .max()
+        .unwrap_or_default()
+        + 2;
+
+    let package_count = usages
+        .iter()
+        .map(|usage| usage.package)
+        .sorted()
+        .dedup()
+        .count();
+
+    writeln!(
+        term,
+        "{} - used by {} {}",
+        style(&args.dependency).bold(),
+        package_count,
+        if package_count == 1 {
+            "package"
+        } else {
+            "packages"
+        }
+    )?;
+    writeln!(term)?;
+
+    for usage in &usages {
+        let features = usage
+            .features()
+            .iter()
+            .map(|name| style_feature(usage.dependency, name))
+            .join(", ");
+
+        writeln!(
+            term,
+            "{}{}",
+            pad_str(&usage.label, width, Alignment::Left, None),
+            if features.is_empty() {
+                style("no features").color256(8).to_string()
+            } else {
+                features
+            }
+        )?;
+    }
+
+    writeln!(term)?;
+    writeln!(
+        term,
+        "{}{}",
+        pad_str("unified", width, Alignment::Left, None),
+        style(unified.keys().join(", ")).bold()
+    )?;
+    writeln!(term)?;
+
+    for (feature, labels) in &unified {
+        writeln!(
+            term,
+            "{}{}",
+            pad_str(feature, width, Alignment::Left, None),
+            style(labels.join(", ")).color256(8)
+        )?;
+    }
+
+    Ok(())
+}
+
+/// default features are marked green - like in the feature selector
+fn style_feature(dependency: &Dependency, name: &str) -> String {
+    match dependency.get_feature(name) {
+        Some(data) if data.is_default => style(name).green().to_string(),
+        _ => name.to_string(),
+    }
+}
+
+/// returns every dependency of the packages on the crate - a package can depend on it multiple times (e.g. as dev-dependency)
+fn get_usages<'a>(packages: impl Iterator<Item = &'a Package>, name: &str) -> Vec<Usage<'a>> {
+    packages
+        .flat_map(|package| {
+            package
+                .dependencies
+                .iter()
+                .filter(|dependency| dependency.name == name && !dependency.is_own_features())
+                .map(|dependency| Usage {
+                    package: &package.name,
+                    label: get_label(package, dependency),
+                    dependency,
+                })
+        })
+        .sorted_by(|usage_a, usage_b| usage_a.label.cmp(&usage_b.label))
+        .collect()
+}
+
+fn get_label(package: &Package, dependency: &Dependency) -> String {
+    let mut label = package.name.to_string();
+
+    match dependency.kind {
+        DependencyType::Development => label.push_str(" (dev)"),
+        DependencyType::Build => label.push_str(" (build)"),
+        _ => {}
+    }
+
+    if let Some(target) = &dependency.target {
+        label.push_str(&format!(" ({})", target));
+    }
+
+    if dependency.workspace {
+        label.push_str(" (workspace)");
+    }
+
+    label
+}
+
+/// returns all features enabled by any of the usages - with the labels of the usages enabling them
+fn unify<'a>(usages: &'a [Usage]) -> BTreeMap<&'a str, Vec<&'a str>> {
+    let mut unified: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+
+    for usage in usages {
+        for feature in usage.features() {
+            unified.entry(feature).or_default().push(&usage.label);
+        }
+    }
+
+    unified
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+
+    use super::*;
+    use crate::project::dependency::builder::DependencyBuilder;
+
+    fn serde() -> DependencyBuilder {
+        DependencyBuilder::new("serde")
+            .feature("default", &["std"])
+            .feature("std", &[])
+            .feature("derive", &[])
+            .feature("rc", &[])
+    }
+
+    fn package(name: &str, dependencies: Vec<Dependency>) -> Package {
+        Package {
+            dependencies,
+            name: name.to_string(),
+            manifest_path: format!("{}/Cargo.toml", name),
+            keep: Default::default(),
+        }
+    }
+
+    fn packages() -> Vec<Package> {
+        vec![
+            package(
+                "b",
+                vec![
+                    serde().enabled(&["derive"]).build().unwrap(),
+                    serde()
+                        .kind(DependencyType::Development)
+                        .default_features(false)
+                        .enabled(&["rc"])
+                        .build()
+                        .unwrap(),
+                ],
+            ),
+            package(
+                "a",
+                vec![
+                    serde().default_features(false).build().unwrap(),
+                    DependencyBuilder::new("other")
+                        .feature("derive", &[])
+                        .enabled(&["derive"])
+                        .build()
+                        .unwrap(),
+                ],
+            ),
+            package("c", vec![]),
+        ]
+    }
+
+    #[test]
+    fn lists_every_package_depending_on_the_crate() {
+        let packages = packages();
+        let usages = get_usages(packages.iter(), "serde");
+
+        assert_eq!(
+            usages
+                .iter()
+                .map(|usage| (usage.label.as_str(), usage.features()))
+                .collect_vec(),
+            [
+                ("a", vec![]),
+                ("b", vec!["derive", "std"]),
+                ("b (dev)", vec!["rc"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unifies_the_features_of_all_packages() {
+        let packages = packages();
+        let usages = get_usages(packages.iter(), "serde");
+
+        assert_eq!(
+            unify(&usages).into_iter().collect_vec(),
+            [
+                ("derive", vec!["b"]),
+                ("rc", vec!["b (dev)"]),
+                ("std", vec!["b"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_crates_are_not_used() {
+        let packages = packages();
+
+        assert!(get_usages(packages.iter(), "tokio").is_empty());
+    }
+}
@@ -1,7 +1,9 @@
 use cargo_platform::Platform;
-use color_eyre::eyre::{bail, eyre, ContextCompat};
+use color_eyre::eyre::{bail, eyre, ContextCompat, WrapErr};
 use std::fs;
+use std::io::Write;
 use std::path::Path;
+use std::process::{Command, Stdio};
 use std::str::FromStr;
 
 pub fn toml_document_from_path<P: AsRef<Path>>(
@@ -10,7 +12,92 @@ pub fn toml_document_from_path<P: AsRef<Path>>(
     let file_content = fs::read_to_string(&dir_path)
         .map_err(|_| eyre!("could not find Cargo.toml at {:?}", dir_path.as_ref()))?;
 
-    Ok(file_content.parse()?)
+    file_content
+        .parse()
+        .wrap_err_with(|| format!("could not parse {:?}", dir_path.as_ref()))
+}
+
+/// returns all features of the manifest which activate the dependency via `dep:`
+pub fn get_features_activating_dependency<P: AsRef<Path>>(
+    manifest_path: P,
+    dependency_name: &str,
+) -> color_eyre::Result<Vec<String>> {
+    let document = toml_document_from_path(manifest_path)?;
+
+    let Some(features) = document
+        .get("features")
+        .and_then(|item| item.as_table_like())
+    else {
+        return Ok(vec![]);
+    };
+
+    let activation = format!("dep:{}", dependency_name);
+
+    Ok(features
+        .iter()
+        .filter(|(_, sub_features)| {
+            sub_features
+                .as_array()
+                .map(|sub_features| {
+                    sub_features
+                        .iter()
+                        .any(|sub_feature| sub_feature.as_str() == Some(&activation))
+                })
+                .unwrap_or(false)
+        })
+        .map(|(name, _)| name.to_string())
+        .collect())
+}
+
+/// returns whether the manifest has a feature with the same name as the dependency which does not activate it via `dep:`
+///
+/// cargo creates an implicit feature for an optional dependency which is never activated via `dep:` - such a feature would collide with it
+pub fn has_feature_named_like_dependency<P: AsRef<Path>>(
+    manifest_path: P,
+    dependency_name: &str,
+) -> color_eyre::Result<bool> {
+    let document = toml_document_from_path(&manifest_path)?;
+
+    let has_feature = document
+        .get("features")
+        .and_then(|item| item.as_table_like())
+        .map_or(false, |features| features.contains_key(dependency_name));
+
+    Ok(has_feature
+        && get_features_activating_dependency(manifest_path, dependency_name)?.is_empty())
+}
+
+/// copies the text to the system clipboard via the first available clipboard tool
+pub fn copy_to_clipboard(text: &str) -> color_eyre::Result<()> {
+    let tools: [(&str, &[&str]); 5] = [
+        ("pbcopy", &[]),
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+        ("clip.exe", &[]),
+    ];
+
+    for (program, args) in tools {
+        let Ok(mut child) = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+        else {
+            continue;
+        };
+
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(text.as_bytes())?;
+        }
+
+        if child.wait()?.success() {
+            return Ok(());
+        }
+    }
+
+    bail!("no clipboard tool was found")
 }
 
 pub fn get_mut_item_from_doc<'a>(
@@ -56,6 +143,14 @@ pub fn get_mut_item_from_doc<'a>(
     Ok(item)
 }
 
+/// a table without its own header - e.g. `[target]` of `[target.'cfg(unix)'.dependencies]`
+pub fn implicit_table() -> toml_edit::Item {
+    let mut table = toml_edit::Table::new();
+    table.set_implicit(true);
+
+    toml_edit::Item::Table(table)
+}
+
 pub fn get_item_from_doc<'a>(
     path: &str,
     document: &'a toml_edit::DocumentMut,
@@ -96,3 +191,79 @@ pub fn get_item_from_doc<'a>(
 
     Ok(item)
 }
+
+/// reads a setting from `workspace.cargo-features-manager` or `cargo-features-manager`
+fn get_config_item<P: AsRef<Path>>(file_path: P, key: &str) -> Option<(String, toml_edit::Item)> {
+    let document = toml_document_from_path(file_path.as_ref().join("Cargo.toml")).ok()?;
+
+    [
+        format!("workspace.cargo-features-manager.{}", key),
+        format!("cargo-features-manager.{}", key),
+    ]
+    .into_iter()
+    .find_map(|item_path| {
+        get_item_from_doc(&item_path, &document)
+            .ok()
+            .map(|item| (item_path, item.clone()))
+    })
+}
+
+pub fn get_config_string<P: AsRef<Path>>(
+    file_path: P,
+    key: &str,
+) -> color_eyre::Result<Option<String>> {
+    get_config_item(file_path, key)
+        .map(|(item_path, item)| {
+            item.as_str()
+                .map(|value| value.to_string())
+                .context(format!("could not parse {} - not a string", item_path))
+        })
+        .transpose()
+}
+
+pub fn get_config_bool<P: AsRef<Path>>(
+    file_path: P,
+    key: &str,
+) -> color_eyre::Result<Option<bool>> {
+    get_config_item(file_path, key)
+        .map(|(item_path, item)| {
+            item.as_bool()
+                .context(format!("could not parse {} - not a boolean", item_path))
+        })
+        .transpose()
+}
+
+/// copies the project of `tests/fixtures/edit` into a temp dir - with `dependency` as the entry of `dep`
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+pub fn fixture_project(dependency: &str) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+
+    copy_dir(Path::new("tests/fixtures/edit"), dir.path());
+
+    let manifest = fs::read_to_string(dir.path().join("Cargo.toml")).unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        manifest.replace("dep = \"0.1\"\n", &format!("{}\n", dependency)),
+    )
+    .unwrap();
+
+    dir
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
@@ -0,0 +1,44 @@
+#![allow(clippy::unwrap_used)]
+
+use cargo_features_manager::{render_dependency, Dependency, Document};
+
+fn document() -> Document {
+    Document::from_manifest_path("tests/fixtures/features/Cargo.toml", true).unwrap()
+}
+
+fn own_features(document: &mut Document) -> &mut Dependency {
+    document
+        .get_package_mut("features")
+        .unwrap()
+        .dependencies
+        .iter_mut()
+        .find(|dependency| dependency.is_own_features())
+        .unwrap()
+}
+
+#[test]
+fn reads_the_features_of_a_project() {
+    let mut document = document();
+
+    assert!(!document.is_workspace());
+
+    let features = own_features(&mut document);
+
+    assert!(features.get_feature("std").unwrap().is_default);
+    assert!(features.get_feature("alloc").unwrap().is_enabled());
+    assert!(!features.get_feature("serde").unwrap().is_enabled());
+}
+
+#[test]
+fn renders_edited_features_without_saving() {
+    let mut document = document();
+
+    let features = own_features(&mut document);
+    features.enable_feature("serde").unwrap();
+    let name = features.get_name();
+
+    assert_eq!(
+        render_dependency(&document, "features", &name).unwrap(),
+        "default = [\"std\", \"serde\"]"
+    );
+}
@@ -0,0 +1,313 @@
+#![allow(clippy::unwrap_used)]
+
+use cargo_features_manager::{save_dependency, Dependency, Document};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use tempfile::TempDir;
+
+/// copies the fixture into a temp dir - with `dependency` as the entry of `dep`
+fn project(dependency: &str) -> TempDir {
+    let dir = tempfile::tempdir().unwrap();
+
+    copy_dir(Path::new("tests/fixtures/edit"), dir.path());
+
+    let manifest = fs::read_to_string(dir.path().join("Cargo.toml")).unwrap();
+    fs::write(
+        dir.path().join("Cargo.toml"),
+        manifest.replace("dep = \"0.1\"\n", &format!("{}\n", dependency)),
+    )
+    .unwrap();
+
+    dir
+}
+
+fn copy_dir(from: &Path, to: &Path) {
+    fs::create_dir_all(to).unwrap();
+
+    for entry in fs::read_dir(from).unwrap() {
+        let entry = entry.unwrap();
+        let target = to.join(entry.file_name());
+
+        if entry.file_type().unwrap().is_dir() {
+            copy_dir(&entry.path(), &target);
+        } else {
+            fs::copy(entry.path(), target).unwrap();
+        }
+    }
+}
+
+/// loads the project, changes `dep`, saves it & returns the written entry
+fn edit(dependency: &str, change: impl FnOnce(&mut Dependency)) -> String {
+    let dir = project(dependency);
+    let manifest_path = dir.path().join("Cargo.toml");
+
+    let mut document = Document::from_manifest_path(&manifest_path, true).unwrap();
+
+    change(
+        document
+            .get_package_mut("edit")
+            .unwrap()
+            .get_dep_mut("dep")
+            .unwrap(),
+    );
+
+    save_dependency(&mut document, "edit", "dep").unwrap();
+
+    fs::read_to_string(manifest_path)
+        .unwrap()
+        .lines()
+        .find(|line| line.starts_with("dep ="))
+        .unwrap()
+        .to_string()
+}
+
+#[test]
+fn a_string_entry_gains_a_feature() {
+    let entry = edit("dep = \"0.1\"", |dep| dep.toggle_feature("serde").unwrap());
+
+    assert_eq!(entry, "dep = { version = \"0.1\", features = [\"serde\"] }");
+}
+
+#[test]
+fn a_table_entry_without_features_collapses_to_a_string() {
+    let entry = edit(
+        "dep = { version = \"0.1\", features = [\"serde\"] }",
+        |dep| dep.toggle_feature("serde").unwrap(),
+    );
+
+    assert_eq!(entry, "dep = \"0.1\"");
+}
+
+#[test]
+fn disabling_a_default_feature_disables_the_default_features() {
+    let entry = edit("dep = \"0.1\"", |dep| dep.toggle_feature("std").unwrap());
+
+    assert_eq!(
+        entry,
+        "dep = { version = \"0.1\", default-features = false }"
+    );
+}
+
+#[test]
+fn enabling_the_default_features_again_removes_default_features() {
+    let entry = edit(
+        "dep = { version = \"0.1\", default-features = false }",
+        |dep| dep.toggle_feature("std").unwrap(),
+    );
+
+    assert_eq!(entry, "dep = \"0.1\"");
+}
+
+#[test]
+fn the_rest_of_the_manifest_is_kept() {
+    let dir = project("dep = \"0.1\"");
+    let manifest_path = dir.path().join("Cargo.toml");
+    let before = fs::read_to_string(&manifest_path).unwrap();
+
+    let mut document = Document::from_manifest_path(&manifest_path, true).unwrap();
+    save_dependency(&mut document, "edit", "dep").unwrap();
+
+    assert_eq!(fs::read_to_string(manifest_path).unwrap(), before);
+}
+
+#[test]
+fn a_path_entry_keeps_its_path_without_a_version() {
+    let entry = edit("dep = { path = \"dep\" }", |dep| {
+        dep.toggle_feature("serde").unwrap()
+    });
+
+    assert_eq!(entry, "dep = { path = \"dep\", features = [\"serde\"] }");
+}
+
+#[test]
+fn a_dependency_that_can_not_be_parsed_is_left_out() {
+    let dir = project("");
+    let manifest_path = dir.path().join("Cargo.toml");
+
+    let manifest = fs::read_to_string(&manifest_path).unwrap();
+    fs::write(
+        &manifest_path,
+        format!(
+            "dependencies = {{ dep = \"0.1\" }}\n\n{}",
+            manifest.replace("[dependencies]\n", "")
+        ),
+    )
+    .unwrap();
+
+    let document = Document::from_manifest_path(&manifest_path, true).unwrap();
+
+    assert!(document
+        .get_package("edit")
+        .unwrap()
+        .get_dep("dep")
+        .is_err());
+    assert_eq!(document.get_load_errors().len(), 1);
+    assert!(document.get_load_errors()[0].starts_with("could not parse dependency dep of edit"));
+}
+
+#[test]
+fn a_package_inheriting_its_fields_from_the_workspace_is_loaded() {
+    let dir = project(
+        "dep = \"0.1\"\n\n[features]\ncli = []\n\n\
+         [workspace]\n\n[workspace.package]\nversion = \"0.1.0\"\nedition = \"2021\"",
+    );
+    let manifest_path = dir.path().join("Cargo.toml");
+
+    let manifest = fs::read_to_string(&manifest_path).unwrap().replace(
+        "version = \"0.1.0\"\nedition = \"2021\"\n\n[dependencies]",
+        "version.workspace = true\nedition = { workspace = true }\n\n[dependencies]",
+    );
+    assert!(manifest.contains("version.workspace = true"));
+    fs::write(&manifest_path, &manifest).unwrap();
+
+    let mut document = Document::from_manifest_path(&manifest_path, true).unwrap();
+    let package = document.get_package_mut("edit").unwrap();
+
+    let own_features = package
+        .dependencies
+        .iter()
+        .find(|dependency| dependency.is_own_features())
+        .unwrap();
+    assert_eq!(own_features.get_version(), "0.1.0");
+
+    package
+        .get_dep_mut("dep")
+        .unwrap()
+        .toggle_feature("serde")
+        .unwrap();
+    save_dependency(&mut document, "edit", "dep").unwrap();
+
+    assert_eq!(
+        fs::read_to_string(&manifest_path).unwrap(),
+        manifest.replace(
+            "dep = \"0.1\"",
+            "dep = { version = \"0.1\", features = [\"serde\"] }"
+        )
+    );
+}
+
+#[test]
+fn a_workspace_root_without_a_package_is_loaded() {
+    let dir = tempfile::tempdir().unwrap();
+    let manifest_path = dir.path().join("Cargo.toml");
+
+    fs::write(&manifest_path, "[workspace]\nmembers = [\"member\"]\n").unwrap();
+
+    fs::create_dir_all(dir.path().join("member/src")).unwrap();
+    fs::write(
+        dir.path().join("member/Cargo.toml"),
+        "[package]\nname = \"member\"\nversion = \"0.1.0\"\nedition = \"2021\"\n",
+    )
+    .unwrap();
+    fs::write(dir.path().join("member/src/lib.rs"), "").unwrap();
+
+    let document = Document::from_manifest_path(&manifest_path, false).unwrap();
+
+    assert_eq!(document.get_packages().len(), 1);
+    assert!(document
+        .get_package("member")
+        .unwrap()
+        .dependencies
+        .is_empty());
+}
+
+#[test]
+fn kept_default_features_are_not_checked() {
+    let dir = project("dep = \"0.1\"\n\n[cargo-features-manager.keep]\ndep = [\"std\"]");
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-features"))
+        .args(["features", "--frozen", "defaults", "--yes"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "nothing to check - no dependency uses default features which are not kept\n"
+    );
+}
+
+#[test]
+fn the_defaults_of_workspace_dependencies_are_checked_in_the_workspace() {
+    let dir = project(
+        "dep = { workspace = true }\n\n[workspace]\n\n[workspace.dependencies]\ndep = \"0.1\"",
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-features"))
+        .args(["features", "--frozen", "defaults", "--yes", "--skip-tests"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    let manifest = fs::read_to_string(dir.path().join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("\ndep = { workspace = true }\n"));
+    assert!(manifest.contains(
+        "[workspace.dependencies]\ndep = { version = \"0.1\", default-features = false }\n"
+    ));
+}
+
+#[test]
+fn disabling_the_defaults_of_a_member_changes_the_workspace_dependency() {
+    let dir = project(
+        "dep = { workspace = true }\n\n[workspace]\n\n[workspace.dependencies]\ndep = \"0.1\"",
+    );
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-features"))
+        .args(["features", "--frozen", "-p", "edit", "--dependency", "dep"])
+        .arg("--no-default-features")
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+
+    let manifest = fs::read_to_string(dir.path().join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("\ndep = { workspace = true }\n"));
+    assert!(manifest.contains(
+        "[workspace.dependencies]\ndep = { version = \"0.1\", default-features = false }\n"
+    ));
+}
+
+#[test]
+fn runs_from_a_nested_directory_of_the_project() {
+    let dir = project(
+        "dep = { version = \"0.1\", features = [\"serde\"] }\n\n\
+         [cargo-features-manager.keep]\ndep = [\"serde\"]",
+    );
+    let nested = dir.path().join("src/nested");
+    fs::create_dir_all(&nested).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-features"))
+        .args(["features", "--frozen", "prune", "--dry-run", "--skip-tests"])
+        .current_dir(&nested)
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    // the kept feature is read from the manifest of the root
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .ends_with("\n- dep: std\n"));
+    // the checks ran from the root
+    assert!(dir.path().join("target").is_dir());
+    assert!(!nested.join("target").exists());
+}
+
+#[test]
+fn completions_list_the_dependencies_without_a_lock_file() {
+    let dir = project("dep = \"0.1\"");
+    fs::remove_file(dir.path().join("Cargo.lock")).unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_cargo-features"))
+        .args(["features", "--list-dependencies"])
+        .current_dir(dir.path())
+        .output()
+        .unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8(output.stdout).unwrap(), "dep\n");
+}